@@ -0,0 +1,139 @@
+//! Measures the throughput of the tokenizer on a large generated file, against the tokenizer it
+//! replaced and against splitting the lines of the file, the least a line-oriented parser does
+//!
+//! Run with `cargo bench --bench tokenizer`, optionally followed by the number of individuals
+//! to generate.
+mod old;
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use gedcom::tokenizer::{Token, Tokenizer};
+
+/// Number of times each tokenizer runs, the fastest run being reported
+const RUNS: usize = 5;
+
+fn main() {
+    let individuals = std::env::args()
+        .skip(1)
+        .find_map(|argument| argument.parse().ok())
+        .unwrap_or(200_000);
+    let contents = generate(individuals);
+    let megabytes = contents.len() as f64 / 1_000_000.0;
+    println!("{individuals} individuals, {megabytes:.1} MB");
+
+    let expected = count_lines(&contents);
+    let results = [
+        ("line splitting", fastest(|| count_lines(&contents))),
+        ("tokenizer", fastest(|| count_tokens(&contents))),
+        (
+            "tokenizer from reader",
+            fastest(|| count_read_tokens(&contents)),
+        ),
+        (
+            "character tokenizer",
+            fastest(|| count_old_tokens(&contents)),
+        ),
+    ];
+    for (name, (count, time)) in results {
+        assert_eq!(count, expected, "{name} found a different number of tokens");
+        let throughput = megabytes / time.as_secs_f64();
+        println!("{name:>24}: {time:>10.2?} {throughput:>8.1} MB/s");
+    }
+}
+
+/// A file of individuals with a name, birth and death each, and families joining them in pairs
+fn generate(individuals: usize) -> String {
+    let mut contents = String::from("0 HEAD\n1 GEDC\n2 VERS 5.5.1\n1 CHAR UTF-8\n");
+    for index in 0..individuals {
+        contents.push_str(&format!(
+            "0 @I{index}@ INDI\n1 NAME Given{index} /Surname{}/\n1 SEX {}\n\
+             1 BIRT\n2 DATE {} JAN {}\n2 PLAC Town {}, County, Country\n\
+             1 DEAT\n2 DATE {}\n1 FAMS @F{}@\n1 NOTE A note on the individual@@home\n",
+            index % 997,
+            if index % 2 == 0 { "M" } else { "F" },
+            index % 28 + 1,
+            1800 + index % 200,
+            index % 101,
+            1860 + index % 200,
+            index / 2,
+        ));
+    }
+    for index in 0..individuals / 2 {
+        contents.push_str(&format!(
+            "0 @F{index}@ FAM\n1 HUSB @I{}@\n1 WIFE @I{}@\n",
+            2 * index,
+            2 * index + 1
+        ));
+    }
+    // the character tokenizer expects no terminator after the last line
+    contents.push_str("0 TRLR");
+    contents
+}
+
+/// Runs the measured function `RUNS` times, returning its result and the fastest time
+fn fastest(mut measured: impl FnMut() -> usize) -> (usize, Duration) {
+    let mut best = (0, Duration::MAX);
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        let count = black_box(measured());
+        best = (count, best.1.min(start.elapsed()));
+    }
+    best
+}
+
+/// Splits each line into its level, pointer, tag and value, counting them
+fn count_lines(contents: &str) -> usize {
+    let mut count = 0;
+    for line in contents.lines() {
+        let mut words = line.splitn(2, ' ');
+        black_box(words.next());
+        count += 1;
+        let mut rest = words.next().unwrap_or_default();
+        if rest.starts_with('@') {
+            let (pointer, after) = rest.split_once(' ').unwrap_or((rest, ""));
+            black_box(pointer);
+            count += 1;
+            rest = after;
+        }
+        let (tag, value) = rest
+            .split_once(' ')
+            .map_or((rest, None), |(tag, value)| (tag, Some(value)));
+        black_box((tag, value));
+        count += 1 + usize::from(value.is_some());
+    }
+    count
+}
+
+fn count_tokens(contents: &str) -> usize {
+    drain(Tokenizer::new(contents))
+}
+
+fn count_read_tokens(contents: &str) -> usize {
+    drain(Tokenizer::from_reader(contents.as_bytes()))
+}
+
+fn drain(mut tokenizer: Tokenizer<'_>) -> usize {
+    let mut count = 0;
+    loop {
+        tokenizer.next_token().unwrap();
+        match tokenizer.current_token() {
+            Token::EOF => return count,
+            token => black_box(token),
+        };
+        count += 1;
+    }
+}
+
+fn count_old_tokens(contents: &str) -> usize {
+    let mut tokenizer = old::Tokenizer::new(contents.chars());
+    let mut count = 0;
+    loop {
+        tokenizer.next_token().unwrap();
+        if tokenizer.done() {
+            return count;
+        }
+        black_box(&tokenizer.current_token);
+        count += 1;
+    }
+}
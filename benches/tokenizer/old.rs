@@ -0,0 +1,118 @@
+//! The tokenizer as it was before it worked on bytes, walking the characters of the contents
+//! and collecting each token into a `String`, kept to compare against
+use std::str::Chars;
+
+#[derive(Debug, PartialEq)]
+pub enum Token {
+    Level(u8),
+    Tag(String),
+    LineValue(String),
+    Pointer(String),
+    Eof,
+    None,
+}
+
+pub struct Tokenizer<'a> {
+    pub current_token: Token,
+    current_char: char,
+    chars: Chars<'a>,
+    pub line: u32,
+    pub level: u8,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub fn new(chars: Chars<'a>) -> Tokenizer<'a> {
+        Tokenizer {
+            current_char: '\n',
+            current_token: Token::None,
+            chars,
+            line: 0,
+            level: 0,
+        }
+    }
+
+    pub fn done(&self) -> bool {
+        self.current_token == Token::Eof
+    }
+
+    pub fn next_token(&mut self) -> Result<(), String> {
+        if self.current_char == '\0' {
+            self.current_token = Token::Eof;
+            return Ok(());
+        }
+
+        if self.current_char == '\r' {
+            self.next_char();
+        }
+        if self.current_char == '\n' {
+            self.next_char();
+            self.line += 1;
+            self.level = self.extract_number()?;
+            self.current_token = Token::Level(self.level);
+            return Ok(());
+        }
+
+        self.skip_whitespace();
+
+        if self.current_char == '\n' {
+            return self.next_token();
+        }
+
+        self.current_token = match self.current_token {
+            Token::Level(_) => {
+                if self.current_char == '@' {
+                    Token::Pointer(self.extract_word())
+                } else {
+                    Token::Tag(self.extract_word())
+                }
+            }
+            Token::Pointer(_) => Token::Tag(self.extract_word()),
+            Token::Tag(_) => Token::LineValue(self.extract_value()),
+            _ => return Err(format!("line {}: {:?}", self.line, self.current_token)),
+        };
+        Ok(())
+    }
+
+    fn next_char(&mut self) {
+        self.current_char = self.chars.next().unwrap_or('\0');
+    }
+
+    fn extract_number(&mut self) -> Result<u8, String> {
+        let mut digits: Vec<char> = Vec::new();
+        while self.current_char.is_ascii_digit() {
+            digits.push(self.current_char);
+            self.next_char();
+        }
+
+        let number = digits.iter().collect::<String>();
+        number
+            .parse::<u8>()
+            .map_err(|_| format!("line {}: invalid level {number}", self.line))
+    }
+
+    fn extract_word(&mut self) -> String {
+        let mut letters: Vec<char> = Vec::new();
+        while !self.current_char.is_whitespace() && self.current_char != '\0' {
+            letters.push(self.current_char);
+            self.next_char();
+        }
+
+        letters.iter().collect::<String>()
+    }
+
+    fn extract_value(&mut self) -> String {
+        let mut letters: Vec<char> = Vec::new();
+        while self.current_char != '\n' {
+            letters.push(self.current_char);
+            self.next_char();
+        }
+
+        letters.iter().collect::<String>()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.current_char.is_whitespace() && self.current_char != '\n' {
+            self.next_char();
+        }
+    }
+}
@@ -1,7 +1,8 @@
 use gedcom::parser::Parser;
 use gedcom::GedcomData;
 use std::env;
-use std::fs;
+use std::fs::{self, File};
+use std::io::BufReader;
 use std::path::PathBuf;
 
 fn main() {
@@ -20,9 +21,12 @@ fn main() {
 
     let data: GedcomData;
 
-    if let Ok(contents) = read_relative(filename) {
-        let mut parser = Parser::new(contents.chars());
-        data = parser.parse_record();
+    if let Ok(file) = open_relative(filename) {
+        let mut parser = Parser::from_reader(BufReader::new(file));
+        data = match parser.parse_record() {
+            Ok(data) => data,
+            Err(err) => return exit_with_error(&err.to_string()),
+        };
 
         println!("Parsing complete!");
         println!("\n\n{:#?}", data);
@@ -32,10 +36,10 @@ fn main() {
     }
 }
 
-fn read_relative(path: &str) -> Result<String, std::io::Error> {
+fn open_relative(path: &str) -> Result<File, std::io::Error> {
     let path_buf: PathBuf = PathBuf::from(path);
     let absolute_path: PathBuf = fs::canonicalize(path_buf)?;
-    fs::read_to_string(absolute_path)
+    File::open(absolute_path)
 }
 
 fn usage(msg: &str) {
@@ -0,0 +1,824 @@
+//! Converts `GedcomData` between the structures of GEDCOM 5.5.1 and 7.0
+//!
+//! The typed model holds the structures of both versions, so converting changes the data in
+//! place to the structures the other version has and `writer::GedcomWriter` writes the syntax
+//! of the version chosen in its `WriterOptions`, _ie._ `CONC` lines and `SNOTE` tags.
+//!
+//! ```rust
+//! use gedcom::convert::convert;
+//! use gedcom::parser::Parser;
+//! use gedcom::types::GedcomVersion;
+//! use gedcom::writer::{GedcomWriter, WriterOptions};
+//!
+//! let source = std::fs::read_to_string("./tests/fixtures/sample.ged").unwrap();
+//! let mut data = Parser::new(&source).parse_record().unwrap();
+//!
+//! for mapping in convert(&mut data, GedcomVersion::V7) {
+//!     println!("{mapping}");
+//! }
+//! let mut writer = GedcomWriter::with_options(WriterOptions {
+//!     version: GedcomVersion::V7,
+//!     ..WriterOptions::default()
+//! });
+//! writer.write_data(&data);
+//! ```
+use std::collections::HashSet;
+use std::fmt::{self, Write};
+use std::mem;
+
+use crate::tree::GedcomData;
+use crate::types::{
+    Event, ExternalId, GedcomMeta, GedcomVersion, Gender, LdsOrdinance, LdsOrdinanceType, Media,
+    MediaFile, MediaLink, NonEvent, SourceCitation,
+};
+use crate::{GedcomNode, Span};
+
+/// The `TYPE` of an `EXID` holding a 5.5.1 permanent record file number (`RFN`)
+const RFN_TYPE: &str = "https://gedcom.io/terms/v7/RFN";
+
+/// The `TYPE` of an `EXID` holding a 5.5.1 automated record id (`RIN`)
+const RIN_TYPE: &str = "https://gedcom.io/terms/v7/RIN";
+
+/// 5.5.1 multimedia formats and the media types GEDCOM 7.0 names them with, the first format of
+/// a media type being the one it converts back to
+const FORMATS: &[(&str, &str)] = &[
+    ("bmp", "image/bmp"),
+    ("gif", "image/gif"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("pcx", "image/vnd.zbrush.pcx"),
+    ("png", "image/png"),
+    ("tif", "image/tiff"),
+    ("tiff", "image/tiff"),
+    ("wav", "audio/wav"),
+    ("mp3", "audio/mpeg"),
+    ("pdf", "application/pdf"),
+    ("txt", "text/plain"),
+    ("html", "text/html"),
+    ("htm", "text/html"),
+];
+
+/// Types of the source a multimedia file was made from, the `MEDI` of GEDCOM 7.0 which 5.5.1
+/// writes in lowercase
+const MEDIA: &[&str] = &[
+    "AUDIO",
+    "BOOK",
+    "CARD",
+    "ELECTRONIC",
+    "FICHE",
+    "FILM",
+    "MAGAZINE",
+    "MANUSCRIPT",
+    "MAP",
+    "NEWSPAPER",
+    "PHOTO",
+    "TOMBSTONE",
+    "VIDEO",
+];
+
+/// Words starting a 5.5.1 date that is not a single date, which cannot be interpreted (`INT`)
+const DATE_MODIFIERS: &[&str] = &["ABT", "CAL", "EST", "BEF", "AFT", "BET", "FROM", "TO"];
+
+/// Data the conversion could not carry over to the other version, or only changed
+#[derive(Clone, Debug, PartialEq)]
+pub struct LossyMapping {
+    /// Cross-reference identifier of the record holding the structure, `None` for the header and
+    /// records without one
+    pub xref: Option<String>,
+    /// Tag of the structure that was changed or removed
+    pub tag: String,
+    /// Human-readable description of what was lost
+    pub message: String,
+}
+
+impl fmt::Display for LossyMapping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.xref {
+            Some(xref) => write!(f, "{} {}: {}", xref, self.tag, self.message),
+            None => write!(f, "{}: {}", self.tag, self.message),
+        }
+    }
+}
+
+/// Converts the data to the structures of the given version, returning every mapping that lost
+/// data on the way
+///
+/// Converting to GEDCOM 7.0:
+/// - declares version 7.0 in the header and removes its `CHAR`, `FILE` and `SUBN`, along with
+///   the submission record
+/// - moves `_UID` extensions to the `unique_ids` of their record, and the `RFN` and `RIN` of
+///   records to `external_ids`
+/// - writes `FILE` paths as URIs and `FORM` formats as media types
+/// - moves date phrases, `(text)` and `INT date (text)`, to the `date_phrase` of events, writes
+///   calendar escapes such as `@#DJULIAN@` as keywords and dual years as the later year
+/// - moves multimedia written in place to new `Media` records
+/// - joins the `CONC` and `CONT` lines of extensions into their values
+///
+/// Converting to GEDCOM 5.5.1 does the reverse, and removes the structures 5.5.1 does not have:
+/// the `SCHMA` of the header, `SDATE`, `NO`, `INIL`, `TRAN`, the `CROP` and `TITL` of
+/// multimedia links, and `EXID` other than those holding an `RFN` or `RIN`. It reports the
+/// nonbinary `SEX X`, which is written as the `N` 5.5.1 does not define.
+pub fn convert(data: &mut GedcomData, version: GedcomVersion) -> Vec<LossyMapping> {
+    let mut converter = Converter::default();
+    match version {
+        GedcomVersion::V7 => converter.upgrade(data),
+        GedcomVersion::V5 => converter.downgrade(data),
+    }
+    converter.report
+}
+
+#[derive(Default)]
+struct Converter {
+    report: Vec<LossyMapping>,
+    /// The record being converted
+    xref: Option<String>,
+    /// Cross-reference identifiers in use, which new records must not take
+    used_xrefs: HashSet<String>,
+    /// Records for the multimedia written in place
+    new_media: Vec<Media>,
+}
+
+impl Converter {
+    fn upgrade(&mut self, data: &mut GedcomData) {
+        self.used_xrefs = used_xrefs(data);
+
+        self.xref = None;
+        let header = data.header.get_or_insert_with(Default::default);
+        header.gedcom = Some(GedcomMeta {
+            version: Some("7.0".to_string()),
+            ..GedcomMeta::default()
+        });
+        header.encoding = None;
+        header.submission_tag = None;
+        if let Some(filename) = header.filename.take() {
+            self.lossy("FILE", format!("the file name {filename} was removed"));
+        }
+        join_continuation_lines(&mut header.extensions);
+        if let Some(submission) = data.submission.take() {
+            self.xref = submission.xref;
+            self.lossy("SUBN", "the submission record was removed".to_string());
+        }
+
+        for submitter in &mut data.submitters {
+            self.xref.clone_from(&submitter.xref);
+            upgrade_unique_ids(&mut submitter.extensions, &mut submitter.unique_ids);
+            let rfn = submitter.record_file_number.take();
+            push_external_id(&mut submitter.external_ids, rfn, RFN_TYPE);
+        }
+        for individual in &mut data.individuals {
+            self.xref.clone_from(&individual.xref);
+            upgrade_unique_ids(&mut individual.extensions, &mut individual.unique_ids);
+            let rfn = individual.record_file_number.take();
+            push_external_id(&mut individual.external_ids, rfn, RFN_TYPE);
+            for event in &mut individual.events {
+                self.upgrade_event(event);
+            }
+            for non_event in &mut individual.non_events {
+                self.upgrade_date(&mut non_event.date, &mut non_event.date_phrase);
+                self.upgrade_citations(&mut non_event.citations);
+            }
+            for ordinance in &mut individual.lds_ordinances {
+                self.upgrade_ordinance_date(ordinance.ordinance, &mut ordinance.date);
+                self.upgrade_citations(&mut ordinance.citations);
+            }
+            self.upgrade_media_links(&mut individual.multimedia);
+        }
+        for family in &mut data.families {
+            self.xref.clone_from(&family.xref);
+            upgrade_unique_ids(&mut family.extensions, &mut family.unique_ids);
+            for event in family.events_mut() {
+                self.upgrade_event(event);
+            }
+            for non_event in &mut family.non_events {
+                self.upgrade_date(&mut non_event.date, &mut non_event.date_phrase);
+                self.upgrade_citations(&mut non_event.citations);
+            }
+            for ordinance in &mut family.lds_ordinances {
+                self.upgrade_ordinance_date(ordinance.ordinance, &mut ordinance.date);
+                self.upgrade_citations(&mut ordinance.citations);
+            }
+            self.upgrade_media_links(&mut family.multimedia);
+        }
+        for repository in &mut data.repositories {
+            self.xref.clone_from(&repository.xref);
+            upgrade_unique_ids(&mut repository.extensions, &mut repository.unique_ids);
+        }
+        for source in &mut data.sources {
+            self.xref.clone_from(&source.xref);
+            upgrade_unique_ids(&mut source.extensions, &mut source.unique_ids);
+            for event in source.data.events_mut() {
+                self.upgrade_event(event);
+            }
+            self.upgrade_media_links(&mut source.multimedia);
+        }
+        for media in &mut data.multimedia {
+            self.xref.clone_from(&media.xref);
+            upgrade_unique_ids(&mut media.extensions, &mut media.unique_ids);
+            let rin = media.automated_record_id.take();
+            push_external_id(&mut media.external_ids, rin, RIN_TYPE);
+            for file in &mut media.files {
+                self.upgrade_file(file);
+            }
+            self.upgrade_citations(&mut media.citations);
+        }
+        for note in &mut data.notes {
+            self.xref.clone_from(&note.xref);
+            upgrade_unique_ids(&mut note.extensions, &mut note.unique_ids);
+            let rin = note.automated_record_id.take();
+            push_external_id(&mut note.external_ids, rin, RIN_TYPE);
+            self.upgrade_citations(&mut note.citations);
+        }
+        join_continuation_lines(&mut data.extensions);
+
+        data.multimedia.append(&mut self.new_media);
+    }
+
+    fn downgrade(&mut self, data: &mut GedcomData) {
+        self.xref = None;
+        let header = data.header.get_or_insert_with(Default::default);
+        header.gedcom = Some(GedcomMeta {
+            version: Some("5.5.1".to_string()),
+            form: Some("LINEAGE-LINKED".to_string()),
+            form_version: None,
+        });
+        for schema_tag in mem::take(&mut header.schema) {
+            let message = format!(
+                "the URI {} of {} was removed",
+                schema_tag.uri, schema_tag.tag
+            );
+            self.lossy("SCHMA", message);
+        }
+
+        for submitter in &mut data.submitters {
+            self.xref.clone_from(&submitter.xref);
+            downgrade_unique_ids(&mut submitter.extensions, &mut submitter.unique_ids);
+            let external_ids = mem::take(&mut submitter.external_ids);
+            let rfn = &mut submitter.record_file_number;
+            self.downgrade_external_ids(external_ids, Some(rfn), None);
+        }
+        for individual in &mut data.individuals {
+            self.xref.clone_from(&individual.xref);
+            downgrade_unique_ids(&mut individual.extensions, &mut individual.unique_ids);
+            let external_ids = mem::take(&mut individual.external_ids);
+            let rfn = &mut individual.record_file_number;
+            self.downgrade_external_ids(external_ids, Some(rfn), None);
+            if matches!(individual.sex, Gender::Nonbinary) {
+                let message = "the sex X is written as N, which GEDCOM 5.5.1 does not define";
+                self.lossy("SEX", message.to_string());
+            }
+            for event in &mut individual.events {
+                self.downgrade_event(event);
+            }
+            self.downgrade_non_events(mem::take(&mut individual.non_events));
+            self.downgrade_ordinances(&mut individual.lds_ordinances);
+            self.downgrade_media_links(&mut individual.multimedia);
+        }
+        for family in &mut data.families {
+            self.xref.clone_from(&family.xref);
+            downgrade_unique_ids(&mut family.extensions, &mut family.unique_ids);
+            let external_ids = mem::take(&mut family.external_ids);
+            self.downgrade_external_ids(external_ids, None, None);
+            for event in family.events_mut() {
+                self.downgrade_event(event);
+            }
+            self.downgrade_non_events(mem::take(&mut family.non_events));
+            self.downgrade_ordinances(&mut family.lds_ordinances);
+            self.downgrade_media_links(&mut family.multimedia);
+        }
+        for repository in &mut data.repositories {
+            self.xref.clone_from(&repository.xref);
+            downgrade_unique_ids(&mut repository.extensions, &mut repository.unique_ids);
+            let external_ids = mem::take(&mut repository.external_ids);
+            self.downgrade_external_ids(external_ids, None, None);
+        }
+        for source in &mut data.sources {
+            self.xref.clone_from(&source.xref);
+            downgrade_unique_ids(&mut source.extensions, &mut source.unique_ids);
+            let external_ids = mem::take(&mut source.external_ids);
+            self.downgrade_external_ids(external_ids, None, None);
+            for event in source.data.events_mut() {
+                self.downgrade_event(event);
+            }
+            self.downgrade_media_links(&mut source.multimedia);
+        }
+        for media in &mut data.multimedia {
+            self.xref.clone_from(&media.xref);
+            downgrade_unique_ids(&mut media.extensions, &mut media.unique_ids);
+            let external_ids = mem::take(&mut media.external_ids);
+            let rin = &mut media.automated_record_id;
+            self.downgrade_external_ids(external_ids, None, Some(rin));
+            for file in &mut media.files {
+                self.downgrade_file(file);
+            }
+            self.downgrade_citations(&mut media.citations);
+        }
+        for note in &mut data.notes {
+            self.xref.clone_from(&note.xref);
+            downgrade_unique_ids(&mut note.extensions, &mut note.unique_ids);
+            let external_ids = mem::take(&mut note.external_ids);
+            let rin = &mut note.automated_record_id;
+            self.downgrade_external_ids(external_ids, None, Some(rin));
+            if let Some(mime) = note.mime.take() {
+                if mime != "text/plain" {
+                    self.lossy(
+                        "MIME",
+                        format!("the media type {mime} of the text was removed"),
+                    );
+                }
+            }
+            if let Some(language) = note.language.take() {
+                self.lossy("LANG", format!("the language {language} was removed"));
+            }
+            for translation in mem::take(&mut note.translations) {
+                let language = translation.language.unwrap_or_default();
+                self.lossy("TRAN", format!("the {language} translation was removed"));
+            }
+            self.downgrade_citations(&mut note.citations);
+        }
+    }
+
+    fn upgrade_event(&mut self, event: &mut Event) {
+        self.upgrade_date(&mut event.date, &mut event.date_phrase);
+        self.upgrade_citations(&mut event.citations);
+        self.upgrade_media_links(&mut event.multimedia);
+        join_continuation_lines(&mut event.extensions);
+    }
+
+    fn downgrade_event(&mut self, event: &mut Event) {
+        self.downgrade_date(&mut event.date, &mut event.date_phrase);
+        if let Some(sort_date) = event.sort_date.take() {
+            let tag = event.event.tag();
+            let message = format!("the sort date {sort_date} of {tag} was removed");
+            self.lossy("SDATE", message);
+        }
+        self.downgrade_citations(&mut event.citations);
+        self.downgrade_media_links(&mut event.multimedia);
+    }
+
+    fn upgrade_citations(&mut self, citations: &mut [SourceCitation]) {
+        for citation in citations {
+            self.upgrade_media_links(&mut citation.multimedia);
+            join_continuation_lines(&mut citation.extensions);
+        }
+    }
+
+    fn downgrade_citations(&mut self, citations: &mut [SourceCitation]) {
+        for citation in citations {
+            self.downgrade_media_links(&mut citation.multimedia);
+        }
+    }
+
+    /// Converts the date of an ordinance, which has no phrase to keep the text of a 5.5.1 date
+    /// phrase in
+    fn upgrade_ordinance_date(&mut self, ordinance: LdsOrdinanceType, date: &mut Option<String>) {
+        let mut phrase = None;
+        self.upgrade_date(date, &mut phrase);
+        if let Some(phrase) = phrase {
+            let message = format!("the date phrase \"{phrase}\" of the ordinance was removed");
+            self.lossy(ordinance.tag(), message);
+        }
+    }
+
+    /// Removes the initiatory ordinances, which GEDCOM 5.5.1 does not have
+    fn downgrade_ordinances(&mut self, ordinances: &mut Vec<LdsOrdinance>) {
+        for mut ordinance in mem::take(ordinances) {
+            if ordinance.ordinance == LdsOrdinanceType::Initiatory {
+                let message = "the initiatory ordinance was removed".to_string();
+                self.lossy(ordinance.ordinance.tag(), message);
+                continue;
+            }
+            self.downgrade_date(&mut ordinance.date, &mut None);
+            self.downgrade_citations(&mut ordinance.citations);
+            ordinances.push(ordinance);
+        }
+    }
+
+    fn downgrade_non_events(&mut self, non_events: Vec<NonEvent>) {
+        for non_event in non_events {
+            let tag = non_event.event.tag();
+            let message = format!("the assertion that {tag} did not happen was removed");
+            self.lossy("NO", message);
+        }
+    }
+
+    /// Splits a 5.5.1 date into a 7.0 date and the phrase describing it: a date phrase,
+    /// `(text)`, is only a phrase and an interpreted date, `INT date (text)`, the date with its
+    /// phrase. Calendar escapes become keywords and a dual year the later year, with the date
+    /// as written kept as the phrase.
+    fn upgrade_date(&mut self, date: &mut Option<String>, phrase: &mut Option<String>) {
+        let Some(written) = date.take() else {
+            return;
+        };
+        let mut value = written.trim();
+        if let Some(start) = value.rfind('(') {
+            if value.ends_with(')') && (start == 0 || value.starts_with("INT ")) {
+                let text = &value[start + 1..value.len() - 1];
+                phrase.get_or_insert_with(|| text.to_string());
+                value = value[..start].trim_start_matches("INT").trim();
+            }
+        }
+
+        let mut unescaped = String::new();
+        let mut rest = value;
+        while let Some(start) = rest.find("@#D") {
+            let Some(length) = rest[start + 3..].find('@') else {
+                break;
+            };
+            unescaped.push_str(&rest[..start]);
+            let calendar = &rest[start + 3..start + 3 + length];
+            unescaped.push_str(&self.upgrade_calendar(calendar));
+            rest = &rest[start + 4 + length..];
+        }
+        unescaped.push_str(rest);
+
+        let mut words = Vec::new();
+        for word in unescaped.split_whitespace() {
+            if word == "B.C." {
+                words.push("BCE".to_string());
+            } else if let Some(year) = dual_year(word) {
+                phrase.get_or_insert_with(|| written.clone());
+                words.push(year.to_string());
+            } else {
+                words.push(word.to_string());
+            }
+        }
+        if !words.is_empty() {
+            *date = Some(words.join(" "));
+        }
+    }
+
+    /// The 7.0 keyword of a 5.5.1 calendar escape, other than `GREGORIAN`, `JULIAN`, `HEBREW`
+    /// and `FRENCH R` written as an extension calendar
+    fn upgrade_calendar(&mut self, calendar: &str) -> String {
+        match calendar {
+            "GREGORIAN" | "JULIAN" | "HEBREW" => calendar.to_string(),
+            "FRENCH R" => "FRENCH_R".to_string(),
+            _ => {
+                let keyword = format!("_{}", calendar.replace(' ', "_"));
+                let message =
+                    format!("the calendar {calendar} is written as the extension {keyword}");
+                self.lossy("DATE", message);
+                keyword
+            }
+        }
+    }
+
+    /// Joins a 7.0 date and its phrase into a 5.5.1 date, interpreting the date from the phrase
+    /// when it is a single date
+    fn downgrade_date(&mut self, date: &mut Option<String>, phrase: &mut Option<String>) {
+        if let Some(value) = date.as_mut() {
+            let mut words = Vec::new();
+            for word in value.split_whitespace() {
+                match word {
+                    "GREGORIAN" | "JULIAN" | "HEBREW" => words.push(format!("@#D{word}@")),
+                    "FRENCH_R" => words.push("@#DFRENCH R@".to_string()),
+                    "BCE" => words.push("B.C.".to_string()),
+                    _ => {
+                        if let Some(calendar) = word.strip_prefix('_') {
+                            if !matches!(calendar, "ROMAN" | "UNKNOWN") {
+                                let message = format!(
+                                    "the extension {word} is not a calendar of GEDCOM 5.5.1"
+                                );
+                                self.lossy("DATE", message);
+                            }
+                            words.push(format!("@#D{}@", calendar.replace('_', " ")));
+                        } else {
+                            words.push(word.to_string());
+                        }
+                    }
+                }
+            }
+            *value = words.join(" ");
+        }
+
+        let Some(text) = phrase.take() else {
+            return;
+        };
+        match date.take() {
+            None => *date = Some(format!("({text})")),
+            Some(value) => {
+                let first_word = value.split_whitespace().next().unwrap_or_default();
+                if DATE_MODIFIERS.contains(&first_word) {
+                    let message = format!("the phrase \"{text}\" of the date {value} was removed");
+                    self.lossy("PHRASE", message);
+                    *date = Some(value);
+                } else {
+                    *date = Some(format!("INT {value} ({text})"));
+                }
+            }
+        }
+    }
+
+    /// Moves multimedia written in place to new records, which GEDCOM 7.0 links to instead
+    fn upgrade_media_links(&mut self, links: &mut [MediaLink]) {
+        for link in links {
+            let MediaLink::Inline { files, title } = link else {
+                continue;
+            };
+            let xref = self.new_xref();
+            let mut media = Media::new(Some(xref.clone()));
+            media.files = mem::take(files);
+            for file in &mut media.files {
+                self.upgrade_file(file);
+            }
+            // the title of the multimedia titles its first file, as it does in 5.5
+            if let Some(file) = media.files.first_mut() {
+                if file.title.is_none() {
+                    file.title = title.take();
+                }
+            }
+            if let Some(title) = title {
+                self.lossy(
+                    "TITL",
+                    format!("the title {title} of the multimedia was removed"),
+                );
+            }
+            *link = MediaLink::Pointer(xref);
+            self.new_media.push(media);
+        }
+    }
+
+    fn downgrade_media_links(&mut self, links: &mut [MediaLink]) {
+        for link in links {
+            match link {
+                MediaLink::Reference { xref, crop, title } => {
+                    if crop.is_some() {
+                        self.lossy("CROP", format!("the crop of {xref} was removed"));
+                    }
+                    if let Some(title) = title {
+                        self.lossy("TITL", format!("the title {title} of {xref} was removed"));
+                    }
+                    *link = MediaLink::Pointer(mem::take(xref));
+                }
+                MediaLink::Inline { files, .. } => {
+                    for file in files {
+                        self.downgrade_file(file);
+                    }
+                }
+                MediaLink::Pointer(_) => (),
+            }
+        }
+    }
+
+    fn upgrade_file(&mut self, file: &mut MediaFile) {
+        if let Some(path) = &mut file.value {
+            *path = path_to_uri(path);
+        }
+        if let Some(form) = &mut file.form {
+            let media_type = FORMATS
+                .iter()
+                .find(|(format, _)| format.eq_ignore_ascii_case(form));
+            if let Some((_, media_type)) = media_type {
+                *form = (*media_type).to_string();
+            } else if !form.contains('/') {
+                let message = format!("the format {form} has no media type");
+                self.lossy("FORM", message);
+            }
+        }
+        if let Some(medium) = &mut file.media_type {
+            let upper = medium.to_uppercase();
+            if MEDIA.contains(&upper.as_str()) || upper == "OTHER" {
+                *medium = upper;
+            } else {
+                let message = format!("the source medium {medium} is written as OTHER");
+                self.lossy("MEDI", message);
+                *medium = "OTHER".to_string();
+            }
+        }
+    }
+
+    fn downgrade_file(&mut self, file: &mut MediaFile) {
+        if let Some(uri) = &mut file.value {
+            *uri = uri_to_path(uri);
+        }
+        if let Some(form) = &mut file.form {
+            let format = FORMATS
+                .iter()
+                .find(|(_, media_type)| media_type.eq_ignore_ascii_case(form));
+            if let Some((format, _)) = format {
+                *form = (*format).to_string();
+            } else {
+                let message = format!("the media type {form} has no GEDCOM 5.5.1 format");
+                self.lossy("FORM", message);
+            }
+        }
+        if let Some(medium) = file.media_type.take() {
+            if MEDIA.contains(&medium.as_str()) {
+                file.media_type = Some(medium.to_lowercase());
+            } else {
+                self.lossy("MEDI", format!("the source medium {medium} was removed"));
+            }
+        }
+        for translation in mem::take(&mut file.translations) {
+            let path = translation.value.unwrap_or_default();
+            self.lossy(
+                "TRAN",
+                format!("the file {path} translating a file was removed"),
+            );
+        }
+    }
+
+    /// Moves the external identifiers holding a 5.5.1 `RFN` or `RIN` to the record's own, and
+    /// removes the others
+    fn downgrade_external_ids(
+        &mut self,
+        external_ids: Vec<ExternalId>,
+        mut rfn: Option<&mut Option<String>>,
+        mut rin: Option<&mut Option<String>>,
+    ) {
+        for external_id in external_ids {
+            let slot = match external_id.id_type.as_deref() {
+                Some(RFN_TYPE) => rfn.as_deref_mut(),
+                Some(RIN_TYPE) => rin.as_deref_mut(),
+                _ => None,
+            };
+            match slot {
+                Some(slot) if slot.is_none() => *slot = external_id.value,
+                _ => {
+                    let message = format!(
+                        "the identifier {} of type {} was removed",
+                        external_id.value.unwrap_or_default(),
+                        external_id.id_type.unwrap_or_default()
+                    );
+                    self.lossy("EXID", message);
+                }
+            }
+        }
+    }
+
+    /// A cross-reference identifier no record has yet
+    fn new_xref(&mut self) -> String {
+        let mut number = self.new_media.len() + 1;
+        loop {
+            let xref = format!("@M{number}@");
+            if self.used_xrefs.insert(xref.clone()) {
+                return xref;
+            }
+            number += 1;
+        }
+    }
+
+    fn lossy(&mut self, tag: &str, message: String) {
+        self.report.push(LossyMapping {
+            xref: self.xref.clone(),
+            tag: tag.to_string(),
+            message,
+        });
+    }
+}
+
+/// Every cross-reference identifier the records of the data have
+fn used_xrefs(data: &GedcomData) -> HashSet<String> {
+    let mut xrefs = HashSet::new();
+    let submission = data.submission.iter().map(|record| &record.xref);
+    let submitters = data.submitters.iter().map(|record| &record.xref);
+    let individuals = data.individuals.iter().map(|record| &record.xref);
+    let families = data.families.iter().map(|record| &record.xref);
+    let repositories = data.repositories.iter().map(|record| &record.xref);
+    let sources = data.sources.iter().map(|record| &record.xref);
+    let multimedia = data.multimedia.iter().map(|record| &record.xref);
+    let notes = data.notes.iter().map(|record| &record.xref);
+    let extensions = data.extensions.iter().map(|node| &node.xref);
+    let all = (submission
+        .chain(submitters)
+        .chain(individuals)
+        .chain(families))
+    .chain(repositories.chain(sources).chain(multimedia))
+    .chain(notes.chain(extensions));
+    for xref in all.flatten() {
+        xrefs.insert(xref.clone());
+    }
+    xrefs
+}
+
+fn upgrade_unique_ids(extensions: &mut Vec<GedcomNode>, unique_ids: &mut Vec<String>) {
+    let (uids, others): (Vec<_>, Vec<_>) = mem::take(extensions)
+        .into_iter()
+        .partition(|node| node.tag == "_UID" && node.value.is_some() && node.children.is_empty());
+    *extensions = others;
+    unique_ids.extend(uids.into_iter().filter_map(|node| node.value));
+    join_continuation_lines(extensions);
+}
+
+fn downgrade_unique_ids(extensions: &mut Vec<GedcomNode>, unique_ids: &mut Vec<String>) {
+    for unique_id in mem::take(unique_ids) {
+        extensions.push(GedcomNode {
+            level: 1,
+            xref: None,
+            tag: "_UID".to_string(),
+            value: Some(unique_id),
+            pointer: false,
+            children: Vec::new(),
+            line: 0,
+            span: Span::default(),
+            raw: None,
+        });
+    }
+}
+
+fn push_external_id(external_ids: &mut Vec<ExternalId>, value: Option<String>, id_type: &str) {
+    if let Some(value) = value {
+        external_ids.push(ExternalId {
+            value: Some(value),
+            id_type: Some(id_type.to_string()),
+        });
+    }
+}
+
+/// Joins the continuation lines of extensions kept with `ParserOptions::keep_line_splits`,
+/// which GEDCOM 7.0 has no `CONC` for
+fn join_continuation_lines(nodes: &mut [GedcomNode]) {
+    for node in nodes {
+        node.join_continuation_lines();
+        join_continuation_lines(&mut node.children);
+    }
+}
+
+/// The later year of a 5.5.1 dual year, _ie._ `1649` for `1648/49`
+fn dual_year(word: &str) -> Option<u32> {
+    let (year, alternative) = word.split_once('/')?;
+    let is_digits = |text: &str| !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit());
+    if !is_digits(year) || !is_digits(alternative) || alternative.len() != 2 {
+        return None;
+    }
+    year.parse::<u32>().ok().map(|year| year + 1)
+}
+
+/// Whether a value starts with a URI scheme, _ie._ `https:`, rather than a path. A drive letter
+/// is not a scheme.
+pub(crate) fn has_scheme(value: &str) -> bool {
+    value.find(':').is_some_and(|end| {
+        let scheme = &value[..end];
+        end > 1
+            && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
+}
+
+/// Whether a path starts with a Windows drive letter, _ie._ `C:`
+fn has_drive(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':'
+}
+
+/// The URI of a 5.5.1 file path: absolute paths become `file` URIs and relative paths
+/// relative references, with backslashes written as slashes
+pub(crate) fn path_to_uri(path: &str) -> String {
+    if has_scheme(path) {
+        return path.to_string();
+    }
+    let path = path.replace('\\', "/");
+    let uri = if path.starts_with("//") {
+        // a network share, `\\server\share`
+        format!("file:{path}")
+    } else if has_drive(&path) {
+        format!("file:///{path}")
+    } else if path.starts_with('/') {
+        format!("file://{path}")
+    } else {
+        path
+    };
+
+    let mut encoded = String::new();
+    for byte in uri.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/:!$&'()*+,;=@".contains(&byte) {
+            encoded.push(char::from(byte));
+        } else {
+            let _ = write!(encoded, "%{byte:02X}");
+        }
+    }
+    encoded
+}
+
+/// The file path of a `file` URI or relative reference, other URIs are kept
+pub(crate) fn uri_to_path(uri: &str) -> String {
+    let path = match uri.strip_prefix("file://") {
+        Some(rest) => match rest.strip_prefix('/') {
+            Some(path) if has_drive(path) => path.to_string(),
+            Some(_) => rest.to_string(),
+            None => format!("//{rest}"),
+        },
+        None if has_scheme(uri) => return uri.to_string(),
+        None => uri.to_string(),
+    };
+
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = path.get(index + 1..index + 3);
+        match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+            Some(byte) if bytes[index] == b'%' => {
+                decoded.push(byte);
+                index += 3;
+            }
+            _ => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,36 @@
+//! Problems reported while leniently parsing a GEDCOM file
+use std::fmt;
+
+/// How serious a reported problem is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    /// Data the parser does not understand was skipped, _ie._ an unknown tag
+    Warning,
+    /// Data that is malformed according to the specification was skipped
+    Error,
+}
+
+/// A problem found in the file that the parser skipped over rather than failing on
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    /// Line number the problem was found at
+    pub line: u32,
+    /// Level of the offending line
+    pub level: u8,
+    /// Tag of the offending line
+    pub tag: String,
+    /// How serious the problem is
+    pub severity: Severity,
+    /// Human-readable description of the problem
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {} ({:?}): {} {}: {}",
+            self.line, self.severity, self.level, self.tag, self.message
+        )
+    }
+}
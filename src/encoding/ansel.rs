@@ -0,0 +1,652 @@
+//! ANSEL (ANSI Z39.47) as written by GEDCOM files, including the GEDCOM additions to the set
+//!
+//! ANSEL writes combining diacritics before the character they modify while Unicode writes
+//! them after, so marks are held until their base character is decoded. The base character
+//! and its marks are then composed into precomposed characters where Unicode has one.
+use std::convert::TryFrom;
+
+/// Decodes ANSEL bytes, replacing undefined bytes with U+FFFD
+pub(crate) fn decode(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len());
+    let mut marks: Vec<char> = Vec::new();
+
+    for &byte in bytes {
+        if let Some(mark) = combining_mark(byte) {
+            marks.push(mark);
+            continue;
+        }
+
+        let base = character(byte);
+        if marks.is_empty() {
+            text.push(base);
+        } else if base.is_control() {
+            // marks without a base character are kept in place
+            text.extend(marks.drain(..));
+            text.push(base);
+        } else {
+            push_composed(&mut text, base, &marks);
+            marks.clear();
+        }
+    }
+    text.extend(marks);
+
+    text
+}
+
+/// Encodes text as ANSEL, writing combining marks before the character they modify. Returns the
+/// byte offset of the first character ANSEL cannot write on failure.
+pub(crate) fn encode(text: &str) -> Result<Vec<u8>, usize> {
+    let mut bytes = Vec::with_capacity(text.len());
+    // the marks following the last base character, which are written before it
+    let mut cluster: Vec<u8> = Vec::new();
+
+    for (offset, character) in text.char_indices() {
+        if let Some(mark) = mark_byte(character) {
+            if cluster.is_empty() {
+                // marks without a base character are kept in place
+                bytes.push(mark);
+            } else {
+                let base = cluster.len() - 1;
+                cluster.insert(base, mark);
+            }
+            continue;
+        }
+        bytes.append(&mut cluster);
+
+        let (base, marks) = decompose(character);
+        let Some(base) = character_byte(base) else {
+            return Err(offset);
+        };
+        for mark in marks {
+            cluster.push(mark_byte(mark).ok_or(offset)?);
+        }
+        cluster.push(base);
+    }
+    bytes.append(&mut cluster);
+
+    Ok(bytes)
+}
+
+/// Splits a precomposed character into its base character and combining marks
+fn decompose(character: char) -> (char, Vec<char>) {
+    let mut base = character;
+    let mut marks = Vec::new();
+    while let Some(&(b, mark, _)) = COMPOSITIONS.iter().find(|&&(_, _, c)| c == base) {
+        base = b;
+        marks.insert(0, mark);
+    }
+    (base, marks)
+}
+
+fn character_byte(character: char) -> Option<u8> {
+    if character.is_ascii() {
+        return u8::try_from(character).ok();
+    }
+    match character {
+        'ß' => Some(0xCF),
+        '\u{FFFD}' => None,
+        _ => (0x80..=0xFF).find(|&byte| self::character(byte) == character),
+    }
+}
+
+fn mark_byte(mark: char) -> Option<u8> {
+    (0xE0..=0xFE).find(|&byte| combining_mark(byte) == Some(mark))
+}
+
+/// Pushes `base` composed with as many of its `marks` as Unicode has precomposed characters
+/// for, followed by the marks left over in canonical order
+fn push_composed(text: &mut String, base: char, marks: &[char]) {
+    let mut marks = marks.to_vec();
+    marks.sort_by_key(|&mark| combining_class(mark));
+
+    let mut composed = base;
+    marks.retain(|&mark| match precomposed(composed, mark) {
+        Some(character) => {
+            composed = character;
+            false
+        }
+        None => true,
+    });
+    text.push(composed);
+    text.extend(marks);
+}
+
+/// Canonical combining class of the marks ANSEL can write, which orders marks below the
+/// character before marks above it
+fn combining_class(mark: char) -> u8 {
+    match mark {
+        '\u{327}' | '\u{328}' => 202,
+        '\u{31C}' | '\u{323}'..='\u{326}' | '\u{32E}' | '\u{332}' | '\u{333}' => 220,
+        '\u{315}' => 232,
+        _ => 230,
+    }
+}
+
+fn precomposed(base: char, mark: char) -> Option<char> {
+    COMPOSITIONS
+        .binary_search_by(|&(b, m, _)| (b, m).cmp(&(base, mark)))
+        .ok()
+        .map(|index| COMPOSITIONS[index].2)
+}
+
+fn combining_mark(byte: u8) -> Option<char> {
+    let mark = match byte {
+        0xE0 => '\u{309}',  // hook above
+        0xE1 => '\u{300}',  // grave
+        0xE2 => '\u{301}',  // acute
+        0xE3 => '\u{302}',  // circumflex
+        0xE4 => '\u{303}',  // tilde
+        0xE5 => '\u{304}',  // macron
+        0xE6 => '\u{306}',  // breve
+        0xE7 => '\u{307}',  // dot above
+        0xE8 => '\u{308}',  // umlaut
+        0xE9 => '\u{30C}',  // caron
+        0xEA => '\u{30A}',  // circle above
+        0xEB => '\u{FE20}', // ligature, left half
+        0xEC => '\u{FE21}', // ligature, right half
+        0xED => '\u{315}',  // high comma, off center
+        0xEE => '\u{30B}',  // double acute
+        0xEF => '\u{310}',  // candrabindu
+        0xF0 => '\u{327}',  // cedilla
+        0xF1 => '\u{328}',  // right hook
+        0xF2 => '\u{323}',  // dot below
+        0xF3 => '\u{324}',  // double dot below
+        0xF4 => '\u{325}',  // circle below
+        0xF5 => '\u{333}',  // double underscore
+        0xF6 => '\u{332}',  // underscore
+        0xF7 => '\u{326}',  // left hook
+        0xF8 => '\u{31C}',  // right cedilla
+        0xF9 => '\u{32E}',  // upadhmaniya
+        0xFA => '\u{FE22}', // double tilde, left half
+        0xFB => '\u{FE23}', // double tilde, right half
+        0xFE => '\u{313}',  // high comma, centered
+        _ => return None,
+    };
+    Some(mark)
+}
+
+fn character(byte: u8) -> char {
+    match byte {
+        0x00..=0x7F => char::from(byte),
+        0x8D => '\u{200D}', // joiner
+        0x8E => '\u{200C}', // nonjoiner
+        0xA1 => 'Ł',
+        0xA2 => 'Ø',
+        0xA3 => 'Đ',
+        0xA4 => 'Þ',
+        0xA5 => 'Æ',
+        0xA6 => 'Œ',
+        0xA7 => 'ʹ',
+        0xA8 => '·',
+        0xA9 => '♭',
+        0xAA => '®',
+        0xAB => '±',
+        0xAC => 'Ơ',
+        0xAD => 'Ư',
+        0xAE => 'ʼ',
+        0xB0 => 'ʻ',
+        0xB1 => 'ł',
+        0xB2 => 'ø',
+        0xB3 => 'đ',
+        0xB4 => 'þ',
+        0xB5 => 'æ',
+        0xB6 => 'œ',
+        0xB7 => 'ʺ',
+        0xB8 => 'ı',
+        0xB9 => '£',
+        0xBA => 'ð',
+        0xBC => 'ơ',
+        0xBD => 'ư',
+        0xBE => '□', // GEDCOM empty box
+        0xBF => '■', // GEDCOM black box
+        0xC0 => '°',
+        0xC1 => 'ℓ',
+        0xC2 => '℗',
+        0xC3 => '©',
+        0xC4 => '♯',
+        0xC5 => '¿',
+        0xC6 => '¡',
+        0xC7 | 0xCF => 'ß', // written as 0xCF by GEDCOM
+        0xC8 => '€',
+        _ => '\u{FFFD}',
+    }
+}
+
+/// Canonical compositions of a character and a combining mark ANSEL can write, sorted by the
+/// character and mark
+const COMPOSITIONS: [(char, char, char); 434] = [
+    ('A', '\u{300}', '\u{c0}'),
+    ('A', '\u{301}', '\u{c1}'),
+    ('A', '\u{302}', '\u{c2}'),
+    ('A', '\u{303}', '\u{c3}'),
+    ('A', '\u{304}', '\u{100}'),
+    ('A', '\u{306}', '\u{102}'),
+    ('A', '\u{307}', '\u{226}'),
+    ('A', '\u{308}', '\u{c4}'),
+    ('A', '\u{309}', '\u{1ea2}'),
+    ('A', '\u{30a}', '\u{c5}'),
+    ('A', '\u{30c}', '\u{1cd}'),
+    ('A', '\u{323}', '\u{1ea0}'),
+    ('A', '\u{325}', '\u{1e00}'),
+    ('A', '\u{328}', '\u{104}'),
+    ('B', '\u{307}', '\u{1e02}'),
+    ('B', '\u{323}', '\u{1e04}'),
+    ('C', '\u{301}', '\u{106}'),
+    ('C', '\u{302}', '\u{108}'),
+    ('C', '\u{307}', '\u{10a}'),
+    ('C', '\u{30c}', '\u{10c}'),
+    ('C', '\u{327}', '\u{c7}'),
+    ('D', '\u{307}', '\u{1e0a}'),
+    ('D', '\u{30c}', '\u{10e}'),
+    ('D', '\u{323}', '\u{1e0c}'),
+    ('D', '\u{327}', '\u{1e10}'),
+    ('E', '\u{300}', '\u{c8}'),
+    ('E', '\u{301}', '\u{c9}'),
+    ('E', '\u{302}', '\u{ca}'),
+    ('E', '\u{303}', '\u{1ebc}'),
+    ('E', '\u{304}', '\u{112}'),
+    ('E', '\u{306}', '\u{114}'),
+    ('E', '\u{307}', '\u{116}'),
+    ('E', '\u{308}', '\u{cb}'),
+    ('E', '\u{309}', '\u{1eba}'),
+    ('E', '\u{30c}', '\u{11a}'),
+    ('E', '\u{323}', '\u{1eb8}'),
+    ('E', '\u{327}', '\u{228}'),
+    ('E', '\u{328}', '\u{118}'),
+    ('F', '\u{307}', '\u{1e1e}'),
+    ('G', '\u{301}', '\u{1f4}'),
+    ('G', '\u{302}', '\u{11c}'),
+    ('G', '\u{304}', '\u{1e20}'),
+    ('G', '\u{306}', '\u{11e}'),
+    ('G', '\u{307}', '\u{120}'),
+    ('G', '\u{30c}', '\u{1e6}'),
+    ('G', '\u{327}', '\u{122}'),
+    ('H', '\u{302}', '\u{124}'),
+    ('H', '\u{307}', '\u{1e22}'),
+    ('H', '\u{308}', '\u{1e26}'),
+    ('H', '\u{30c}', '\u{21e}'),
+    ('H', '\u{323}', '\u{1e24}'),
+    ('H', '\u{327}', '\u{1e28}'),
+    ('H', '\u{32e}', '\u{1e2a}'),
+    ('I', '\u{300}', '\u{cc}'),
+    ('I', '\u{301}', '\u{cd}'),
+    ('I', '\u{302}', '\u{ce}'),
+    ('I', '\u{303}', '\u{128}'),
+    ('I', '\u{304}', '\u{12a}'),
+    ('I', '\u{306}', '\u{12c}'),
+    ('I', '\u{307}', '\u{130}'),
+    ('I', '\u{308}', '\u{cf}'),
+    ('I', '\u{309}', '\u{1ec8}'),
+    ('I', '\u{30c}', '\u{1cf}'),
+    ('I', '\u{323}', '\u{1eca}'),
+    ('I', '\u{328}', '\u{12e}'),
+    ('J', '\u{302}', '\u{134}'),
+    ('K', '\u{301}', '\u{1e30}'),
+    ('K', '\u{30c}', '\u{1e8}'),
+    ('K', '\u{323}', '\u{1e32}'),
+    ('K', '\u{327}', '\u{136}'),
+    ('L', '\u{301}', '\u{139}'),
+    ('L', '\u{30c}', '\u{13d}'),
+    ('L', '\u{323}', '\u{1e36}'),
+    ('L', '\u{327}', '\u{13b}'),
+    ('M', '\u{301}', '\u{1e3e}'),
+    ('M', '\u{307}', '\u{1e40}'),
+    ('M', '\u{323}', '\u{1e42}'),
+    ('N', '\u{300}', '\u{1f8}'),
+    ('N', '\u{301}', '\u{143}'),
+    ('N', '\u{303}', '\u{d1}'),
+    ('N', '\u{307}', '\u{1e44}'),
+    ('N', '\u{30c}', '\u{147}'),
+    ('N', '\u{323}', '\u{1e46}'),
+    ('N', '\u{327}', '\u{145}'),
+    ('O', '\u{300}', '\u{d2}'),
+    ('O', '\u{301}', '\u{d3}'),
+    ('O', '\u{302}', '\u{d4}'),
+    ('O', '\u{303}', '\u{d5}'),
+    ('O', '\u{304}', '\u{14c}'),
+    ('O', '\u{306}', '\u{14e}'),
+    ('O', '\u{307}', '\u{22e}'),
+    ('O', '\u{308}', '\u{d6}'),
+    ('O', '\u{309}', '\u{1ece}'),
+    ('O', '\u{30b}', '\u{150}'),
+    ('O', '\u{30c}', '\u{1d1}'),
+    ('O', '\u{323}', '\u{1ecc}'),
+    ('O', '\u{328}', '\u{1ea}'),
+    ('P', '\u{301}', '\u{1e54}'),
+    ('P', '\u{307}', '\u{1e56}'),
+    ('R', '\u{301}', '\u{154}'),
+    ('R', '\u{307}', '\u{1e58}'),
+    ('R', '\u{30c}', '\u{158}'),
+    ('R', '\u{323}', '\u{1e5a}'),
+    ('R', '\u{327}', '\u{156}'),
+    ('S', '\u{301}', '\u{15a}'),
+    ('S', '\u{302}', '\u{15c}'),
+    ('S', '\u{307}', '\u{1e60}'),
+    ('S', '\u{30c}', '\u{160}'),
+    ('S', '\u{323}', '\u{1e62}'),
+    ('S', '\u{326}', '\u{218}'),
+    ('S', '\u{327}', '\u{15e}'),
+    ('T', '\u{307}', '\u{1e6a}'),
+    ('T', '\u{30c}', '\u{164}'),
+    ('T', '\u{323}', '\u{1e6c}'),
+    ('T', '\u{326}', '\u{21a}'),
+    ('T', '\u{327}', '\u{162}'),
+    ('U', '\u{300}', '\u{d9}'),
+    ('U', '\u{301}', '\u{da}'),
+    ('U', '\u{302}', '\u{db}'),
+    ('U', '\u{303}', '\u{168}'),
+    ('U', '\u{304}', '\u{16a}'),
+    ('U', '\u{306}', '\u{16c}'),
+    ('U', '\u{308}', '\u{dc}'),
+    ('U', '\u{309}', '\u{1ee6}'),
+    ('U', '\u{30a}', '\u{16e}'),
+    ('U', '\u{30b}', '\u{170}'),
+    ('U', '\u{30c}', '\u{1d3}'),
+    ('U', '\u{323}', '\u{1ee4}'),
+    ('U', '\u{324}', '\u{1e72}'),
+    ('U', '\u{328}', '\u{172}'),
+    ('V', '\u{303}', '\u{1e7c}'),
+    ('V', '\u{323}', '\u{1e7e}'),
+    ('W', '\u{300}', '\u{1e80}'),
+    ('W', '\u{301}', '\u{1e82}'),
+    ('W', '\u{302}', '\u{174}'),
+    ('W', '\u{307}', '\u{1e86}'),
+    ('W', '\u{308}', '\u{1e84}'),
+    ('W', '\u{323}', '\u{1e88}'),
+    ('X', '\u{307}', '\u{1e8a}'),
+    ('X', '\u{308}', '\u{1e8c}'),
+    ('Y', '\u{300}', '\u{1ef2}'),
+    ('Y', '\u{301}', '\u{dd}'),
+    ('Y', '\u{302}', '\u{176}'),
+    ('Y', '\u{303}', '\u{1ef8}'),
+    ('Y', '\u{304}', '\u{232}'),
+    ('Y', '\u{307}', '\u{1e8e}'),
+    ('Y', '\u{308}', '\u{178}'),
+    ('Y', '\u{309}', '\u{1ef6}'),
+    ('Y', '\u{323}', '\u{1ef4}'),
+    ('Z', '\u{301}', '\u{179}'),
+    ('Z', '\u{302}', '\u{1e90}'),
+    ('Z', '\u{307}', '\u{17b}'),
+    ('Z', '\u{30c}', '\u{17d}'),
+    ('Z', '\u{323}', '\u{1e92}'),
+    ('a', '\u{300}', '\u{e0}'),
+    ('a', '\u{301}', '\u{e1}'),
+    ('a', '\u{302}', '\u{e2}'),
+    ('a', '\u{303}', '\u{e3}'),
+    ('a', '\u{304}', '\u{101}'),
+    ('a', '\u{306}', '\u{103}'),
+    ('a', '\u{307}', '\u{227}'),
+    ('a', '\u{308}', '\u{e4}'),
+    ('a', '\u{309}', '\u{1ea3}'),
+    ('a', '\u{30a}', '\u{e5}'),
+    ('a', '\u{30c}', '\u{1ce}'),
+    ('a', '\u{323}', '\u{1ea1}'),
+    ('a', '\u{325}', '\u{1e01}'),
+    ('a', '\u{328}', '\u{105}'),
+    ('b', '\u{307}', '\u{1e03}'),
+    ('b', '\u{323}', '\u{1e05}'),
+    ('c', '\u{301}', '\u{107}'),
+    ('c', '\u{302}', '\u{109}'),
+    ('c', '\u{307}', '\u{10b}'),
+    ('c', '\u{30c}', '\u{10d}'),
+    ('c', '\u{327}', '\u{e7}'),
+    ('d', '\u{307}', '\u{1e0b}'),
+    ('d', '\u{30c}', '\u{10f}'),
+    ('d', '\u{323}', '\u{1e0d}'),
+    ('d', '\u{327}', '\u{1e11}'),
+    ('e', '\u{300}', '\u{e8}'),
+    ('e', '\u{301}', '\u{e9}'),
+    ('e', '\u{302}', '\u{ea}'),
+    ('e', '\u{303}', '\u{1ebd}'),
+    ('e', '\u{304}', '\u{113}'),
+    ('e', '\u{306}', '\u{115}'),
+    ('e', '\u{307}', '\u{117}'),
+    ('e', '\u{308}', '\u{eb}'),
+    ('e', '\u{309}', '\u{1ebb}'),
+    ('e', '\u{30c}', '\u{11b}'),
+    ('e', '\u{323}', '\u{1eb9}'),
+    ('e', '\u{327}', '\u{229}'),
+    ('e', '\u{328}', '\u{119}'),
+    ('f', '\u{307}', '\u{1e1f}'),
+    ('g', '\u{301}', '\u{1f5}'),
+    ('g', '\u{302}', '\u{11d}'),
+    ('g', '\u{304}', '\u{1e21}'),
+    ('g', '\u{306}', '\u{11f}'),
+    ('g', '\u{307}', '\u{121}'),
+    ('g', '\u{30c}', '\u{1e7}'),
+    ('g', '\u{327}', '\u{123}'),
+    ('h', '\u{302}', '\u{125}'),
+    ('h', '\u{307}', '\u{1e23}'),
+    ('h', '\u{308}', '\u{1e27}'),
+    ('h', '\u{30c}', '\u{21f}'),
+    ('h', '\u{323}', '\u{1e25}'),
+    ('h', '\u{327}', '\u{1e29}'),
+    ('h', '\u{32e}', '\u{1e2b}'),
+    ('i', '\u{300}', '\u{ec}'),
+    ('i', '\u{301}', '\u{ed}'),
+    ('i', '\u{302}', '\u{ee}'),
+    ('i', '\u{303}', '\u{129}'),
+    ('i', '\u{304}', '\u{12b}'),
+    ('i', '\u{306}', '\u{12d}'),
+    ('i', '\u{308}', '\u{ef}'),
+    ('i', '\u{309}', '\u{1ec9}'),
+    ('i', '\u{30c}', '\u{1d0}'),
+    ('i', '\u{323}', '\u{1ecb}'),
+    ('i', '\u{328}', '\u{12f}'),
+    ('j', '\u{302}', '\u{135}'),
+    ('j', '\u{30c}', '\u{1f0}'),
+    ('k', '\u{301}', '\u{1e31}'),
+    ('k', '\u{30c}', '\u{1e9}'),
+    ('k', '\u{323}', '\u{1e33}'),
+    ('k', '\u{327}', '\u{137}'),
+    ('l', '\u{301}', '\u{13a}'),
+    ('l', '\u{30c}', '\u{13e}'),
+    ('l', '\u{323}', '\u{1e37}'),
+    ('l', '\u{327}', '\u{13c}'),
+    ('m', '\u{301}', '\u{1e3f}'),
+    ('m', '\u{307}', '\u{1e41}'),
+    ('m', '\u{323}', '\u{1e43}'),
+    ('n', '\u{300}', '\u{1f9}'),
+    ('n', '\u{301}', '\u{144}'),
+    ('n', '\u{303}', '\u{f1}'),
+    ('n', '\u{307}', '\u{1e45}'),
+    ('n', '\u{30c}', '\u{148}'),
+    ('n', '\u{323}', '\u{1e47}'),
+    ('n', '\u{327}', '\u{146}'),
+    ('o', '\u{300}', '\u{f2}'),
+    ('o', '\u{301}', '\u{f3}'),
+    ('o', '\u{302}', '\u{f4}'),
+    ('o', '\u{303}', '\u{f5}'),
+    ('o', '\u{304}', '\u{14d}'),
+    ('o', '\u{306}', '\u{14f}'),
+    ('o', '\u{307}', '\u{22f}'),
+    ('o', '\u{308}', '\u{f6}'),
+    ('o', '\u{309}', '\u{1ecf}'),
+    ('o', '\u{30b}', '\u{151}'),
+    ('o', '\u{30c}', '\u{1d2}'),
+    ('o', '\u{323}', '\u{1ecd}'),
+    ('o', '\u{328}', '\u{1eb}'),
+    ('p', '\u{301}', '\u{1e55}'),
+    ('p', '\u{307}', '\u{1e57}'),
+    ('r', '\u{301}', '\u{155}'),
+    ('r', '\u{307}', '\u{1e59}'),
+    ('r', '\u{30c}', '\u{159}'),
+    ('r', '\u{323}', '\u{1e5b}'),
+    ('r', '\u{327}', '\u{157}'),
+    ('s', '\u{301}', '\u{15b}'),
+    ('s', '\u{302}', '\u{15d}'),
+    ('s', '\u{307}', '\u{1e61}'),
+    ('s', '\u{30c}', '\u{161}'),
+    ('s', '\u{323}', '\u{1e63}'),
+    ('s', '\u{326}', '\u{219}'),
+    ('s', '\u{327}', '\u{15f}'),
+    ('t', '\u{307}', '\u{1e6b}'),
+    ('t', '\u{308}', '\u{1e97}'),
+    ('t', '\u{30c}', '\u{165}'),
+    ('t', '\u{323}', '\u{1e6d}'),
+    ('t', '\u{326}', '\u{21b}'),
+    ('t', '\u{327}', '\u{163}'),
+    ('u', '\u{300}', '\u{f9}'),
+    ('u', '\u{301}', '\u{fa}'),
+    ('u', '\u{302}', '\u{fb}'),
+    ('u', '\u{303}', '\u{169}'),
+    ('u', '\u{304}', '\u{16b}'),
+    ('u', '\u{306}', '\u{16d}'),
+    ('u', '\u{308}', '\u{fc}'),
+    ('u', '\u{309}', '\u{1ee7}'),
+    ('u', '\u{30a}', '\u{16f}'),
+    ('u', '\u{30b}', '\u{171}'),
+    ('u', '\u{30c}', '\u{1d4}'),
+    ('u', '\u{323}', '\u{1ee5}'),
+    ('u', '\u{324}', '\u{1e73}'),
+    ('u', '\u{328}', '\u{173}'),
+    ('v', '\u{303}', '\u{1e7d}'),
+    ('v', '\u{323}', '\u{1e7f}'),
+    ('w', '\u{300}', '\u{1e81}'),
+    ('w', '\u{301}', '\u{1e83}'),
+    ('w', '\u{302}', '\u{175}'),
+    ('w', '\u{307}', '\u{1e87}'),
+    ('w', '\u{308}', '\u{1e85}'),
+    ('w', '\u{30a}', '\u{1e98}'),
+    ('w', '\u{323}', '\u{1e89}'),
+    ('x', '\u{307}', '\u{1e8b}'),
+    ('x', '\u{308}', '\u{1e8d}'),
+    ('y', '\u{300}', '\u{1ef3}'),
+    ('y', '\u{301}', '\u{fd}'),
+    ('y', '\u{302}', '\u{177}'),
+    ('y', '\u{303}', '\u{1ef9}'),
+    ('y', '\u{304}', '\u{233}'),
+    ('y', '\u{307}', '\u{1e8f}'),
+    ('y', '\u{308}', '\u{ff}'),
+    ('y', '\u{309}', '\u{1ef7}'),
+    ('y', '\u{30a}', '\u{1e99}'),
+    ('y', '\u{323}', '\u{1ef5}'),
+    ('z', '\u{301}', '\u{17a}'),
+    ('z', '\u{302}', '\u{1e91}'),
+    ('z', '\u{307}', '\u{17c}'),
+    ('z', '\u{30c}', '\u{17e}'),
+    ('z', '\u{323}', '\u{1e93}'),
+    ('\u{c2}', '\u{300}', '\u{1ea6}'),
+    ('\u{c2}', '\u{301}', '\u{1ea4}'),
+    ('\u{c2}', '\u{303}', '\u{1eaa}'),
+    ('\u{c2}', '\u{309}', '\u{1ea8}'),
+    ('\u{c4}', '\u{304}', '\u{1de}'),
+    ('\u{c5}', '\u{301}', '\u{1fa}'),
+    ('\u{c6}', '\u{301}', '\u{1fc}'),
+    ('\u{c6}', '\u{304}', '\u{1e2}'),
+    ('\u{c7}', '\u{301}', '\u{1e08}'),
+    ('\u{ca}', '\u{300}', '\u{1ec0}'),
+    ('\u{ca}', '\u{301}', '\u{1ebe}'),
+    ('\u{ca}', '\u{303}', '\u{1ec4}'),
+    ('\u{ca}', '\u{309}', '\u{1ec2}'),
+    ('\u{cf}', '\u{301}', '\u{1e2e}'),
+    ('\u{d4}', '\u{300}', '\u{1ed2}'),
+    ('\u{d4}', '\u{301}', '\u{1ed0}'),
+    ('\u{d4}', '\u{303}', '\u{1ed6}'),
+    ('\u{d4}', '\u{309}', '\u{1ed4}'),
+    ('\u{d5}', '\u{301}', '\u{1e4c}'),
+    ('\u{d5}', '\u{304}', '\u{22c}'),
+    ('\u{d5}', '\u{308}', '\u{1e4e}'),
+    ('\u{d6}', '\u{304}', '\u{22a}'),
+    ('\u{d8}', '\u{301}', '\u{1fe}'),
+    ('\u{dc}', '\u{300}', '\u{1db}'),
+    ('\u{dc}', '\u{301}', '\u{1d7}'),
+    ('\u{dc}', '\u{304}', '\u{1d5}'),
+    ('\u{dc}', '\u{30c}', '\u{1d9}'),
+    ('\u{e2}', '\u{300}', '\u{1ea7}'),
+    ('\u{e2}', '\u{301}', '\u{1ea5}'),
+    ('\u{e2}', '\u{303}', '\u{1eab}'),
+    ('\u{e2}', '\u{309}', '\u{1ea9}'),
+    ('\u{e4}', '\u{304}', '\u{1df}'),
+    ('\u{e5}', '\u{301}', '\u{1fb}'),
+    ('\u{e6}', '\u{301}', '\u{1fd}'),
+    ('\u{e6}', '\u{304}', '\u{1e3}'),
+    ('\u{e7}', '\u{301}', '\u{1e09}'),
+    ('\u{ea}', '\u{300}', '\u{1ec1}'),
+    ('\u{ea}', '\u{301}', '\u{1ebf}'),
+    ('\u{ea}', '\u{303}', '\u{1ec5}'),
+    ('\u{ea}', '\u{309}', '\u{1ec3}'),
+    ('\u{ef}', '\u{301}', '\u{1e2f}'),
+    ('\u{f4}', '\u{300}', '\u{1ed3}'),
+    ('\u{f4}', '\u{301}', '\u{1ed1}'),
+    ('\u{f4}', '\u{303}', '\u{1ed7}'),
+    ('\u{f4}', '\u{309}', '\u{1ed5}'),
+    ('\u{f5}', '\u{301}', '\u{1e4d}'),
+    ('\u{f5}', '\u{304}', '\u{22d}'),
+    ('\u{f5}', '\u{308}', '\u{1e4f}'),
+    ('\u{f6}', '\u{304}', '\u{22b}'),
+    ('\u{f8}', '\u{301}', '\u{1ff}'),
+    ('\u{fc}', '\u{300}', '\u{1dc}'),
+    ('\u{fc}', '\u{301}', '\u{1d8}'),
+    ('\u{fc}', '\u{304}', '\u{1d6}'),
+    ('\u{fc}', '\u{30c}', '\u{1da}'),
+    ('\u{102}', '\u{300}', '\u{1eb0}'),
+    ('\u{102}', '\u{301}', '\u{1eae}'),
+    ('\u{102}', '\u{303}', '\u{1eb4}'),
+    ('\u{102}', '\u{309}', '\u{1eb2}'),
+    ('\u{103}', '\u{300}', '\u{1eb1}'),
+    ('\u{103}', '\u{301}', '\u{1eaf}'),
+    ('\u{103}', '\u{303}', '\u{1eb5}'),
+    ('\u{103}', '\u{309}', '\u{1eb3}'),
+    ('\u{112}', '\u{300}', '\u{1e14}'),
+    ('\u{112}', '\u{301}', '\u{1e16}'),
+    ('\u{113}', '\u{300}', '\u{1e15}'),
+    ('\u{113}', '\u{301}', '\u{1e17}'),
+    ('\u{14c}', '\u{300}', '\u{1e50}'),
+    ('\u{14c}', '\u{301}', '\u{1e52}'),
+    ('\u{14d}', '\u{300}', '\u{1e51}'),
+    ('\u{14d}', '\u{301}', '\u{1e53}'),
+    ('\u{15a}', '\u{307}', '\u{1e64}'),
+    ('\u{15b}', '\u{307}', '\u{1e65}'),
+    ('\u{160}', '\u{307}', '\u{1e66}'),
+    ('\u{161}', '\u{307}', '\u{1e67}'),
+    ('\u{168}', '\u{301}', '\u{1e78}'),
+    ('\u{169}', '\u{301}', '\u{1e79}'),
+    ('\u{16a}', '\u{308}', '\u{1e7a}'),
+    ('\u{16b}', '\u{308}', '\u{1e7b}'),
+    ('\u{17f}', '\u{307}', '\u{1e9b}'),
+    ('\u{1a0}', '\u{300}', '\u{1edc}'),
+    ('\u{1a0}', '\u{301}', '\u{1eda}'),
+    ('\u{1a0}', '\u{303}', '\u{1ee0}'),
+    ('\u{1a0}', '\u{309}', '\u{1ede}'),
+    ('\u{1a0}', '\u{323}', '\u{1ee2}'),
+    ('\u{1a1}', '\u{300}', '\u{1edd}'),
+    ('\u{1a1}', '\u{301}', '\u{1edb}'),
+    ('\u{1a1}', '\u{303}', '\u{1ee1}'),
+    ('\u{1a1}', '\u{309}', '\u{1edf}'),
+    ('\u{1a1}', '\u{323}', '\u{1ee3}'),
+    ('\u{1af}', '\u{300}', '\u{1eea}'),
+    ('\u{1af}', '\u{301}', '\u{1ee8}'),
+    ('\u{1af}', '\u{303}', '\u{1eee}'),
+    ('\u{1af}', '\u{309}', '\u{1eec}'),
+    ('\u{1af}', '\u{323}', '\u{1ef0}'),
+    ('\u{1b0}', '\u{300}', '\u{1eeb}'),
+    ('\u{1b0}', '\u{301}', '\u{1ee9}'),
+    ('\u{1b0}', '\u{303}', '\u{1eef}'),
+    ('\u{1b0}', '\u{309}', '\u{1eed}'),
+    ('\u{1b0}', '\u{323}', '\u{1ef1}'),
+    ('\u{1b7}', '\u{30c}', '\u{1ee}'),
+    ('\u{1ea}', '\u{304}', '\u{1ec}'),
+    ('\u{1eb}', '\u{304}', '\u{1ed}'),
+    ('\u{226}', '\u{304}', '\u{1e0}'),
+    ('\u{227}', '\u{304}', '\u{1e1}'),
+    ('\u{228}', '\u{306}', '\u{1e1c}'),
+    ('\u{229}', '\u{306}', '\u{1e1d}'),
+    ('\u{22e}', '\u{304}', '\u{230}'),
+    ('\u{22f}', '\u{304}', '\u{231}'),
+    ('\u{292}', '\u{30c}', '\u{1ef}'),
+    ('\u{1e36}', '\u{304}', '\u{1e38}'),
+    ('\u{1e37}', '\u{304}', '\u{1e39}'),
+    ('\u{1e5a}', '\u{304}', '\u{1e5c}'),
+    ('\u{1e5b}', '\u{304}', '\u{1e5d}'),
+    ('\u{1e62}', '\u{307}', '\u{1e68}'),
+    ('\u{1e63}', '\u{307}', '\u{1e69}'),
+    ('\u{1ea0}', '\u{302}', '\u{1eac}'),
+    ('\u{1ea0}', '\u{306}', '\u{1eb6}'),
+    ('\u{1ea1}', '\u{302}', '\u{1ead}'),
+    ('\u{1ea1}', '\u{306}', '\u{1eb7}'),
+    ('\u{1eb8}', '\u{302}', '\u{1ec6}'),
+    ('\u{1eb9}', '\u{302}', '\u{1ec7}'),
+    ('\u{1ecc}', '\u{302}', '\u{1ed8}'),
+    ('\u{1ecd}', '\u{302}', '\u{1ed9}'),
+];
@@ -0,0 +1,418 @@
+//! Decoding of the character sets a GEDCOM file can be written in
+//!
+//! The `Parser` works on decoded text. Files that are not UTF-8 are decoded according to their
+//! byte order mark or the `CHAR` tag of their header first, which `Parser::from_bytes` does for
+//! you:
+//!
+//! ```rust
+//! use gedcom::encoding;
+//! use gedcom::parser::Parser;
+//!
+//! let bytes = b"0 HEAD\n1 CHAR ANSEL\n0 @I1@ INDI\n1 NAME Ren\xe2e /Dupont/\n0 TRLR";
+//! let contents = encoding::decode(bytes).unwrap();
+//!
+//! let data = Parser::new(&contents).parse_record().unwrap();
+//! let name = data.individuals[0].name.as_ref().unwrap();
+//! assert_eq!(name.value.as_ref().unwrap(), "René /Dupont/");
+//! ```
+use std::borrow::Cow;
+use std::convert::TryFrom;
+
+use crate::error::GedcomError;
+use crate::tokenizer::{count_terminators, terminator_length};
+
+mod ansel;
+mod code_page;
+mod reader;
+pub(crate) use reader::LineDecoder;
+
+/// Character set of a file, the `CHAR` tag of the header
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Charset {
+    /// ANSI Z39.47 with the GEDCOM additions, the default of GEDCOM 5.5.1
+    Ansel,
+    /// 7-bit ASCII
+    Ascii,
+    /// UTF-8, the only character set of GEDCOM 5.5.5 and 7
+    #[default]
+    Utf8,
+    /// UTF-16, declared as `UNICODE`
+    Unicode,
+    /// Windows-1252, not allowed by the specification but declared by many programs
+    Ansi,
+    /// IBM code page 437, not allowed by the specification but declared by some programs
+    IbmPc,
+    /// Mac OS Roman, not allowed by the specification but declared by some programs
+    Macintosh,
+}
+
+impl Charset {
+    /// The character set named by a `CHAR` value, or `None` if the name is not known
+    #[must_use]
+    pub fn from_declared(value: &str) -> Option<Charset> {
+        let charset = match value.trim().to_ascii_uppercase().as_str() {
+            "ANSEL" => Charset::Ansel,
+            "ASCII" | "US-ASCII" => Charset::Ascii,
+            "UTF-8" | "UTF8" => Charset::Utf8,
+            "UNICODE" | "UTF-16" => Charset::Unicode,
+            "ANSI" | "WINDOWS-1252" | "CP1252" | "ISO-8859-1" | "LATIN1" => Charset::Ansi,
+            "IBMPC" | "IBM PC" | "CP437" => Charset::IbmPc,
+            "MACINTOSH" | "MACROMAN" => Charset::Macintosh,
+            _ => return None,
+        };
+        Some(charset)
+    }
+
+    /// The name a `CHAR` tag declares the character set with
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Charset::Ansel => "ANSEL",
+            Charset::Ascii => "ASCII",
+            Charset::Utf8 => "UTF-8",
+            Charset::Unicode => "UNICODE",
+            Charset::Ansi => "ANSI",
+            Charset::IbmPc => "IBMPC",
+            Charset::Macintosh => "MACINTOSH",
+        }
+    }
+}
+
+/// How the text of a file is laid out in bytes: its character set along with the byte order
+/// mark and byte order it is written with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileEncoding {
+    /// The character set of the text
+    pub charset: Charset,
+    /// Whether the bytes start with a byte order mark, which only UTF-8 and UTF-16 have
+    pub byte_order_mark: bool,
+    /// Whether UTF-16 is written with the most significant byte of each unit first
+    pub big_endian: bool,
+}
+
+impl FileEncoding {
+    /// The bytes of the byte order mark, empty without one
+    pub(crate) fn byte_order_mark_bytes(self) -> &'static [u8] {
+        match (self.byte_order_mark, self.charset, self.big_endian) {
+            (true, Charset::Utf8, _) => &[0xEF, 0xBB, 0xBF],
+            (true, Charset::Unicode, true) => &[0xFE, 0xFF],
+            (true, Charset::Unicode, false) => &[0xFF, 0xFE],
+            _ => &[],
+        }
+    }
+}
+
+impl From<Charset> for FileEncoding {
+    /// The way a file is written in the character set by default, UTF-16 being little-endian
+    /// with a byte order mark
+    fn from(charset: Charset) -> FileEncoding {
+        FileEncoding {
+            charset,
+            byte_order_mark: charset == Charset::Unicode,
+            big_endian: false,
+        }
+    }
+}
+
+impl Default for FileEncoding {
+    fn default() -> FileEncoding {
+        FileEncoding::from(Charset::default())
+    }
+}
+
+/// The encoding of an undecoded file, as `decode` detects it.
+/// UTF-8 and UTF-16 are detected from a byte order mark or the bytes of the opening `0 HEAD`,
+/// other character sets are read from the `CHAR` tag of the header and default to UTF-8.
+#[must_use]
+pub fn detect(bytes: &[u8]) -> FileEncoding {
+    detect_unicode(bytes).unwrap_or_else(|| FileEncoding {
+        charset: (declared_charset(bytes))
+            .and_then(Charset::from_declared)
+            .unwrap_or_default(),
+        byte_order_mark: false,
+        big_endian: false,
+    })
+}
+
+/// The encoding detected from a byte order mark or the bytes of the opening `0 HEAD`, `None`
+/// when the bytes start with neither
+pub(crate) fn detect_unicode(bytes: &[u8]) -> Option<FileEncoding> {
+    let (charset, byte_order_mark, big_endian) = match bytes {
+        [0xEF, 0xBB, 0xBF, ..] => (Charset::Utf8, true, false),
+        [0xFE, 0xFF, ..] => (Charset::Unicode, true, true),
+        [0xFF, 0xFE, ..] => (Charset::Unicode, true, false),
+        [0, b'0', ..] => (Charset::Unicode, false, true),
+        [b'0', 0, ..] => (Charset::Unicode, false, false),
+        _ => return None,
+    };
+    Some(FileEncoding {
+        charset,
+        byte_order_mark,
+        big_endian,
+    })
+}
+
+/// The value of the `CHAR` tag in the header of an undecoded file.
+/// Only finds the tag in character sets that write ASCII as single bytes, so not in UTF-16.
+#[must_use]
+pub fn declared_charset(bytes: &[u8]) -> Option<&str> {
+    find_declared_charset(bytes).map(|(_, value)| value)
+}
+
+/// The line number and value of the `CHAR` tag in the header
+fn find_declared_charset(bytes: &[u8]) -> Option<(u32, &str)> {
+    for (index, line) in lines(bytes).enumerate() {
+        let line = line.trim_ascii();
+        if index > 0 && line.starts_with(b"0") {
+            // end of the header
+            return None;
+        }
+        if let Some(value) = charset_value(line) {
+            let line_number = u32::try_from(index + 1).unwrap_or(u32::MAX);
+            return std::str::from_utf8(value)
+                .ok()
+                .map(|value| (line_number, value));
+        }
+    }
+    None
+}
+
+/// The value of a line if it is the level 1 `CHAR` tag, its level, tag and value being
+/// delimited by any whitespace like the `Tokenizer` allows
+pub(crate) fn charset_value(line: &[u8]) -> Option<&[u8]> {
+    let (level, rest) = split_word(line.trim_ascii());
+    let (tag, value) = split_word(rest);
+    (level == b"1" && tag == b"CHAR").then_some(value)
+}
+
+/// Splits the first word from the bytes, along with the rest without its leading whitespace
+fn split_word(bytes: &[u8]) -> (&[u8], &[u8]) {
+    let end = (bytes.iter())
+        .position(u8::is_ascii_whitespace)
+        .unwrap_or(bytes.len());
+    (&bytes[..end], bytes[end..].trim_ascii_start())
+}
+
+/// Splits bytes at any of the `\r`, `\n`, `\r\n` and `\n\r` line terminators
+fn lines(bytes: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let mut rest = Some(bytes);
+    std::iter::from_fn(move || {
+        let bytes = rest?;
+        let end = bytes
+            .iter()
+            .position(|&byte| byte == b'\r' || byte == b'\n');
+        rest = end.map(|end| &bytes[end + terminator_length(&bytes[end..])..]);
+        Some(&bytes[..end.unwrap_or(bytes.len())])
+    })
+}
+
+/// Decodes a file, borrowing the bytes when they need no transcoding.
+///
+/// UTF-8 and UTF-16 are detected from a byte order mark, which is removed, or from the bytes of
+/// the opening `0 HEAD`. Other files are decoded according to the `CHAR` tag of their header
+/// and read as UTF-8 without a known one. `detect` tells how the file was encoded, to `encode`
+/// the text back the same way.
+///
+/// # Errors
+///
+/// Returns a `GedcomError` if the bytes are not valid in the character set, or if the detected
+/// encoding contradicts the `CHAR` tag.
+pub fn decode(bytes: &[u8]) -> Result<Cow<'_, str>, GedcomError> {
+    let Some(encoding) = detect_unicode(bytes) else {
+        let charset = declared_charset(bytes)
+            .and_then(Charset::from_declared)
+            .unwrap_or(Charset::Utf8);
+        if charset == Charset::Unicode {
+            // UTF-16 would have been detected from the opening line
+            check_declared_charset(bytes, Charset::Utf8)?;
+        }
+        return decode_as(bytes, charset);
+    };
+    let rest = &bytes[encoding.byte_order_mark_bytes().len()..];
+    if encoding.charset == Charset::Unicode {
+        return decode_detected_utf16(rest, encoding.big_endian);
+    }
+    let text = decode_utf8(rest)?;
+    check_declared_charset(text.as_bytes(), Charset::Utf8)?;
+    Ok(Cow::Borrowed(text))
+}
+
+fn decode_detected_utf16(bytes: &[u8], big_endian: bool) -> Result<Cow<'_, str>, GedcomError> {
+    let text = decode_utf16(bytes, big_endian)?;
+    check_declared_charset(text.as_bytes(), Charset::Unicode)?;
+    Ok(Cow::Owned(text))
+}
+
+/// Fails if the `CHAR` tag names a known character set other than the `detected` one.
+/// ASCII files are also valid UTF-8.
+fn check_declared_charset(text: &[u8], detected: Charset) -> Result<(), GedcomError> {
+    match find_declared_charset(text) {
+        Some((line, declared)) => check_charset(line, declared, detected),
+        None => Ok(()),
+    }
+}
+
+/// Fails if `declared` names a known character set other than the `detected` one
+fn check_charset(line: u32, declared: &str, detected: Charset) -> Result<(), GedcomError> {
+    let matches = match Charset::from_declared(declared) {
+        None => true,
+        Some(Charset::Ascii) => detected == Charset::Utf8,
+        Some(charset) => charset == detected,
+    };
+    if matches {
+        return Ok(());
+    }
+    Err(GedcomError::EncodingMismatch {
+        line,
+        declared: declared.to_string(),
+        detected: match detected {
+            Charset::Unicode => "UTF-16",
+            _ => "UTF-8",
+        },
+    })
+}
+
+/// Decodes a file written in the given character set, borrowing the bytes when they need no
+/// transcoding. `ASCII` files that are not are read as UTF-8 or else as Windows-1252, which is
+/// what programs declaring `ASCII` tend to write.
+///
+/// # Errors
+///
+/// Returns a `GedcomError` if the bytes are not valid in the character set.
+pub fn decode_as(bytes: &[u8], charset: Charset) -> Result<Cow<'_, str>, GedcomError> {
+    if charset != Charset::Unicode && bytes.is_ascii() {
+        return Ok(Cow::Borrowed(
+            std::str::from_utf8(bytes).unwrap_or_default(),
+        ));
+    }
+
+    let text = match charset {
+        Charset::Utf8 => return decode_utf8(bytes).map(Cow::Borrowed),
+        Charset::Ascii => match std::str::from_utf8(bytes) {
+            Ok(text) => return Ok(Cow::Borrowed(text)),
+            Err(_) => bytes
+                .iter()
+                .map(|&byte| code_page::windows_1252(byte))
+                .collect(),
+        },
+        Charset::Unicode => match bytes {
+            [0xFE, 0xFF, rest @ ..] => decode_utf16(rest, true)?,
+            [0xFF, 0xFE, rest @ ..] => decode_utf16(rest, false)?,
+            _ => decode_utf16(bytes, false)?,
+        },
+        Charset::Ansel => ansel::decode(bytes),
+        Charset::Ansi => bytes
+            .iter()
+            .map(|&byte| code_page::windows_1252(byte))
+            .collect(),
+        Charset::IbmPc => bytes.iter().map(|&byte| code_page::cp437(byte)).collect(),
+        Charset::Macintosh => bytes
+            .iter()
+            .map(|&byte| code_page::mac_roman(byte))
+            .collect(),
+    };
+    Ok(Cow::Owned(text))
+}
+
+/// Encodes text in the given character set, or with the byte order mark and byte order of a
+/// `FileEncoding`. A character set alone writes UTF-16 little-endian with a byte order mark.
+///
+/// # Errors
+///
+/// Returns a `GedcomError` if the text has a character the character set cannot write.
+pub fn encode(text: &str, encoding: impl Into<FileEncoding>) -> Result<Vec<u8>, GedcomError> {
+    let encoding = encoding.into();
+    let charset = encoding.charset;
+    let code_page = match charset {
+        Charset::Utf8 | Charset::Unicode => {
+            let mut bytes = encoding.byte_order_mark_bytes().to_vec();
+            if charset == Charset::Utf8 {
+                bytes.extend_from_slice(text.as_bytes());
+            } else if encoding.big_endian {
+                bytes.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+            } else {
+                bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+            }
+            return Ok(bytes);
+        }
+        Charset::Ansel => {
+            return ansel::encode(text).map_err(|offset| unencodable(text, offset, charset));
+        }
+        Charset::Ascii => |byte| char::from(byte & 0x7F),
+        Charset::Ansi => code_page::windows_1252,
+        Charset::IbmPc => code_page::cp437,
+        Charset::Macintosh => code_page::mac_roman,
+    };
+    text.char_indices()
+        .map(
+            |(offset, character)| match code_page::encode(character, code_page) {
+                Some(byte) if charset != Charset::Ascii || byte.is_ascii() => Ok(byte),
+                _ => Err(unencodable(text, offset, charset)),
+            },
+        )
+        .collect()
+}
+
+fn unencodable(text: &str, offset: usize, charset: Charset) -> GedcomError {
+    GedcomError::InvalidEncoding {
+        line: line_at(text.as_bytes(), offset),
+        encoding: charset.name().to_string(),
+    }
+}
+
+fn decode_utf8(bytes: &[u8]) -> Result<&str, GedcomError> {
+    std::str::from_utf8(bytes).map_err(|error| GedcomError::InvalidEncoding {
+        line: line_at(bytes, error.valid_up_to()),
+        encoding: "UTF-8".to_string(),
+    })
+}
+
+/// Decodes UTF-16, failing on an odd final byte at the line of the last complete unit
+fn decode_utf16(bytes: &[u8], big_endian: bool) -> Result<String, GedcomError> {
+    let units = bytes.chunks_exact(2).map(|pair| {
+        let pair = [pair[0], pair[1]];
+        if big_endian {
+            u16::from_be_bytes(pair)
+        } else {
+            u16::from_le_bytes(pair)
+        }
+    });
+
+    let mut text = String::with_capacity(bytes.len() / 2);
+    let mut line = 1;
+    let mut unit_line = 1;
+    let invalid = |line| GedcomError::InvalidEncoding {
+        line,
+        encoding: "UTF-16".to_string(),
+    };
+    // the terminator started by the last character, which the other one would end
+    let mut terminator = None;
+    for unit in char::decode_utf16(units) {
+        let character = unit.map_err(|_| invalid(line))?;
+        unit_line = line;
+        // lines end at `\r`, `\n`, `\r\n` or `\n\r`, as `terminator_length` reads them
+        terminator = match (terminator, character) {
+            (Some('\r'), '\n') | (Some('\n'), '\r') => {
+                // the second character of the terminator ends the same line as the first
+                unit_line = line - 1;
+                None
+            }
+            (_, '\r' | '\n') => {
+                line += 1;
+                Some(character)
+            }
+            _ => None,
+        };
+        text.push(character);
+    }
+    if bytes.len() % 2 == 1 {
+        return Err(invalid(unit_line));
+    }
+    Ok(text)
+}
+
+/// The line number of the byte at `offset`
+fn line_at(bytes: &[u8], offset: usize) -> u32 {
+    let lines = count_terminators(&bytes[..offset]) + 1;
+    u32::try_from(lines).unwrap_or(u32::MAX)
+}
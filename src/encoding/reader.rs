@@ -0,0 +1,274 @@
+//! Decoding of a file read one line at a time
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::io::BufRead;
+
+use crate::encoding::{
+    charset_value, check_charset, decode_as, detect_unicode, Charset, FileEncoding,
+};
+use crate::error::GedcomError;
+
+/// Reads decoded lines from a reader, detecting the encoding the same way `decode` does
+///
+/// UTF-8 and UTF-16 are detected from a byte order mark or the bytes of the opening `0 HEAD`.
+/// Otherwise the lines of the header are read ahead up to its `CHAR` tag, and every line is
+/// decoded in the character set it names, UTF-8 without a known one.
+pub(crate) struct LineDecoder<'a> {
+    reader: Box<dyn BufRead + 'a>,
+    /// Character set of the lines, `None` until it is detected or declared
+    charset: Option<Charset>,
+    /// The encoding detected from the bytes, `None` when it is declared instead
+    detected: Option<FileEncoding>,
+    /// Number of lines read so far
+    line: u32,
+    in_header: bool,
+    bytes: Vec<u8>,
+    /// Lines of the header read ahead to find its `CHAR` tag, not yet decoded
+    pending: VecDeque<Vec<u8>>,
+}
+
+impl<'a> LineDecoder<'a> {
+    pub(crate) fn new(reader: impl BufRead + 'a) -> LineDecoder<'a> {
+        LineDecoder {
+            reader: Box::new(reader),
+            charset: None,
+            detected: None,
+            line: 0,
+            in_header: true,
+            bytes: Vec::new(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Replaces `text` with the next line including its terminator, which is empty at the end
+    /// of the file
+    pub(crate) fn read_line(&mut self, text: &mut String) -> Result<(), GedcomError> {
+        text.clear();
+        if self.line == 0 {
+            self.detect_encoding()?;
+        }
+        self.line += 1;
+
+        if self.charset == Some(Charset::Unicode) {
+            return self.read_utf16_line(text);
+        }
+
+        let line = self.line;
+        if self.charset.is_none() {
+            self.read_header_ahead()?;
+        }
+        if let Some(bytes) = self.pending.pop_front() {
+            self.bytes = bytes;
+        } else {
+            self.bytes.clear();
+            self.read_line_bytes()
+                .map_err(|error| io_error(line, &error))?;
+        }
+        let charset = self.charset.unwrap_or_default();
+        let decoded = decode_as(&self.bytes, charset).map_err(|error| match error {
+            GedcomError::InvalidEncoding { encoding, .. } => {
+                GedcomError::InvalidEncoding { line, encoding }
+            }
+            error => error,
+        })?;
+        text.push_str(&decoded);
+
+        self.check_declared_charset(text)
+    }
+
+    /// Reads the lines of the header ahead up to its `CHAR` tag, to decode the lines before it
+    /// in the character set it names. Files without a known one are read as UTF-8.
+    fn read_header_ahead(&mut self) -> Result<(), GedcomError> {
+        let mut charset = None;
+        loop {
+            let line = self.line + u32::try_from(self.pending.len()).unwrap_or(u32::MAX);
+            self.bytes.clear();
+            self.read_line_bytes()
+                .map_err(|error| io_error(line, &error))?;
+            let bytes = std::mem::take(&mut self.bytes);
+            if bytes.is_empty() {
+                break;
+            }
+            let trimmed = bytes.trim_ascii();
+            let ends_header = line > 1 && trimmed.starts_with(b"0");
+            let declared = charset_value(trimmed).filter(|_| !ends_header);
+            let is_declared = declared.is_some();
+            if let Some(declared) = declared {
+                let declared = String::from_utf8_lossy(declared);
+                charset = Charset::from_declared(&declared);
+                if charset == Some(Charset::Unicode) {
+                    // UTF-16 would have been detected from the opening line
+                    check_charset(line, &declared, Charset::Utf8)?;
+                }
+            }
+            self.pending.push_back(bytes);
+            if ends_header || is_declared {
+                break;
+            }
+        }
+        self.charset = Some(charset.unwrap_or(Charset::Utf8));
+        self.in_header = false;
+        Ok(())
+    }
+
+    /// Reads the bytes of the next line up to any of the `\r`, `\n`, `\r\n` and `\n\r`
+    /// terminators
+    fn read_line_bytes(&mut self) -> std::io::Result<()> {
+        loop {
+            let buffer = self.reader.fill_buf()?;
+            if buffer.is_empty() {
+                return Ok(());
+            }
+            let Some(index) = buffer.iter().position(|&byte| is_terminator(byte)) else {
+                let length = buffer.len();
+                self.bytes.extend_from_slice(buffer);
+                self.reader.consume(length);
+                continue;
+            };
+            let terminator = buffer[index];
+            self.bytes.extend_from_slice(&buffer[..=index]);
+            self.reader.consume(index + 1);
+            let buffer = self.reader.fill_buf()?;
+            if let Some(&next) = buffer.first() {
+                if is_terminator(next) && next != terminator {
+                    self.bytes.push(next);
+                    self.reader.consume(1);
+                }
+            }
+            return Ok(());
+        }
+    }
+
+    fn detect_encoding(&mut self) -> Result<(), GedcomError> {
+        let buffer = self
+            .reader
+            .fill_buf()
+            .map_err(|error| io_error(1, &error))?;
+        let Some(encoding) = detect_unicode(buffer) else {
+            return Ok(());
+        };
+        self.reader.consume(encoding.byte_order_mark_bytes().len());
+        self.charset = Some(encoding.charset);
+        self.detected = Some(encoding);
+        Ok(())
+    }
+
+    /// The encoding of the file as detected or declared so far, UTF-8 until then
+    pub(crate) fn encoding(&self) -> FileEncoding {
+        self.detected.unwrap_or(FileEncoding {
+            charset: self.charset.unwrap_or_default(),
+            byte_order_mark: false,
+            big_endian: false,
+        })
+    }
+
+    /// Whether a UTF-16 file is big-endian
+    fn big_endian(&self) -> bool {
+        self.detected.is_some_and(|encoding| encoding.big_endian)
+    }
+
+    fn read_utf16_line(&mut self, text: &mut String) -> Result<(), GedcomError> {
+        let line = self.line;
+        let mut units = Vec::new();
+        loop {
+            let mut pair = [0; 2];
+            match self.read_pair(&mut pair) {
+                Ok(0) => break,
+                Ok(1) => {
+                    // an odd final byte, reported at the line of the last complete unit
+                    return Err(GedcomError::InvalidEncoding {
+                        line: if units.is_empty() { line - 1 } else { line }.max(1),
+                        encoding: "UTF-16".to_string(),
+                    });
+                }
+                Ok(_) => (),
+                Err(error) => return Err(io_error(line, &error)),
+            }
+            let unit = if self.big_endian() {
+                u16::from_be_bytes(pair)
+            } else {
+                u16::from_le_bytes(pair)
+            };
+            units.push(unit);
+            if let Ok(byte) = u8::try_from(unit) {
+                if is_terminator(byte) {
+                    self.read_utf16_terminator_pair(byte, &mut units);
+                    break;
+                }
+            }
+        }
+
+        for unit in char::decode_utf16(units) {
+            let Ok(character) = unit else {
+                return Err(GedcomError::InvalidEncoding {
+                    line,
+                    encoding: "UTF-16".to_string(),
+                });
+            };
+            text.push(character);
+        }
+
+        self.check_declared_charset(text)
+    }
+
+    /// Reads the two bytes of a unit, returning how many could be read before the end of the file
+    fn read_pair(&mut self, pair: &mut [u8; 2]) -> std::io::Result<usize> {
+        let mut length = 0;
+        while length < pair.len() {
+            match self.reader.read(&mut pair[length..]) {
+                Ok(0) => break,
+                Ok(read) => length += read,
+                Err(error) if error.kind() == std::io::ErrorKind::Interrupted => (),
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(length)
+    }
+
+    /// Takes the second unit of a `\r\n` or `\n\r` terminator when it is already buffered
+    fn read_utf16_terminator_pair(&mut self, terminator: u8, units: &mut Vec<u16>) {
+        let Ok(buffer) = self.reader.fill_buf() else {
+            return;
+        };
+        let Some(&[first, second]) = buffer.get(..2) else {
+            return;
+        };
+        let pair = [first, second];
+        let unit = if self.big_endian() {
+            u16::from_be_bytes(pair)
+        } else {
+            u16::from_le_bytes(pair)
+        };
+        if u8::try_from(unit).is_ok_and(|byte| is_terminator(byte) && byte != terminator) {
+            units.push(unit);
+            self.reader.consume(2);
+        }
+    }
+
+    /// Fails if the `CHAR` tag of the header contradicts the encoding detected from the bytes
+    fn check_declared_charset(&mut self, text: &str) -> Result<(), GedcomError> {
+        if !self.in_header {
+            return Ok(());
+        }
+        let line = text.trim();
+        if self.line > 1 && line.starts_with('0') {
+            self.in_header = false;
+        } else if let Some(declared) = charset_value(line.as_bytes()) {
+            let declared = std::str::from_utf8(declared).unwrap_or_default();
+            let detected = self.charset.unwrap_or(Charset::Utf8);
+            check_charset(self.line, declared, detected)?;
+        }
+        Ok(())
+    }
+}
+
+fn is_terminator(byte: u8) -> bool {
+    byte == b'\r' || byte == b'\n'
+}
+
+fn io_error(line: u32, error: &std::io::Error) -> GedcomError {
+    GedcomError::Io {
+        line,
+        message: error.to_string(),
+    }
+}
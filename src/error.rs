@@ -0,0 +1,157 @@
+//! Errors that can occur while parsing a GEDCOM file
+use std::fmt;
+use std::path::PathBuf;
+
+/// The error returned when a GEDCOM file cannot be parsed
+///
+/// Every variant found in the text of a file carries the line number at which the problem was
+/// found and, where it applies, the kind of record or structure that was being parsed at the
+/// time. Errors of the files and archives around it carry no line.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum GedcomError {
+    /// A tag that is not handled within the structure being parsed
+    UnexpectedTag {
+        /// Line number of the offending tag
+        line: u32,
+        /// The kind of record being parsed, _ie._ `Individual`
+        record: &'static str,
+        /// The offending tag
+        tag: String,
+    },
+    /// A token found where another kind of token was expected
+    UnexpectedToken {
+        /// Line number of the offending token
+        line: u32,
+        /// The kind of record being parsed, _ie._ `Individual`
+        record: &'static str,
+        /// Debug representation of the offending token
+        token: String,
+    },
+    /// A line value that could not be interpreted for its tag
+    InvalidValue {
+        /// Line number of the offending value
+        line: u32,
+        /// The kind of record being parsed, _ie._ `Individual`
+        record: &'static str,
+        /// The tag the value belongs to
+        tag: String,
+        /// The offending value
+        value: String,
+    },
+    /// A tag that requires a line value but has none
+    MissingValue {
+        /// Line number of the offending tag
+        line: u32,
+        /// The tag missing its value
+        tag: String,
+    },
+    /// Bytes that are not valid in the character set of the file, or text it cannot be written in
+    InvalidEncoding {
+        /// Line number of the invalid bytes
+        line: u32,
+        /// Name of the character set, _ie._ `UTF-8`
+        encoding: String,
+    },
+    /// A `CHAR` tag naming a character set other than the one the file is written in
+    EncodingMismatch {
+        /// Line number of the `CHAR` tag
+        line: u32,
+        /// The character set named by the tag
+        declared: String,
+        /// The character set detected from the bytes of the file, _ie._ `UTF-16`
+        detected: &'static str,
+    },
+    /// A failure to read the GEDCOM file
+    Io {
+        /// Line number being read
+        line: u32,
+        /// Description of the underlying `std::io::Error`
+        message: String,
+    },
+    /// A line that does not start with a valid level number
+    InvalidLevel {
+        /// Line number of the offending level
+        line: u32,
+        /// The text found in place of the level
+        value: String,
+    },
+    /// A failure to read a file other than the GEDCOM file, _ie._ a GEDZIP package or the media
+    /// files bundled in one
+    File {
+        /// Path of the file
+        path: PathBuf,
+        /// Description of the underlying `std::io::Error`
+        message: String,
+    },
+    /// A GEDZIP package that is not a zip archive this crate can read or has no `gedcom.ged`
+    InvalidArchive {
+        /// Description of the problem
+        message: String,
+    },
+    /// A cross-reference identifier or pointer that does not follow the `@` alphanumeric `@`
+    /// syntax of at most 20 characters between the `@` signs
+    InvalidPointer {
+        /// Line number of the offending pointer
+        line: u32,
+        /// The offending pointer
+        value: String,
+    },
+}
+
+impl GedcomError {
+    /// The line number at which the error occurred, `None` for errors of files and archives
+    #[must_use]
+    pub fn line(&self) -> Option<u32> {
+        match self {
+            GedcomError::UnexpectedTag { line, .. }
+            | GedcomError::UnexpectedToken { line, .. }
+            | GedcomError::InvalidValue { line, .. }
+            | GedcomError::MissingValue { line, .. }
+            | GedcomError::InvalidEncoding { line, .. }
+            | GedcomError::EncodingMismatch { line, .. }
+            | GedcomError::Io { line, .. }
+            | GedcomError::InvalidLevel { line, .. }
+            | GedcomError::InvalidPointer { line, .. } => Some(*line),
+            GedcomError::File { .. } | GedcomError::InvalidArchive { .. } => None,
+        }
+    }
+
+    /// Description of the error without its line number
+    pub(crate) fn message(&self) -> String {
+        match self {
+            GedcomError::UnexpectedTag { record, tag, .. } => {
+                format!("Unhandled {record} Tag: {tag}")
+            }
+            GedcomError::UnexpectedToken { record, token, .. } => {
+                format!("Unhandled {record} Token: {token}")
+            }
+            GedcomError::InvalidValue {
+                record, tag, value, ..
+            } => format!("Invalid {record} {tag} value: {value}"),
+            GedcomError::MissingValue { tag, .. } => format!("Missing {tag} value"),
+            GedcomError::InvalidEncoding { encoding, .. } => format!("Invalid {encoding} text"),
+            GedcomError::EncodingMismatch {
+                declared, detected, ..
+            } => format!("Declared character set {declared} but the file is {detected}"),
+            GedcomError::Io { message, .. } => format!("Could not read file: {message}"),
+            GedcomError::File { path, message } => {
+                format!("Could not read {}: {message}", path.display())
+            }
+            GedcomError::InvalidLevel { value, .. } => format!("Invalid level: {value:?}"),
+            GedcomError::InvalidArchive { message, .. } => format!("Invalid archive: {message}"),
+            GedcomError::InvalidPointer { value, .. } => format!("Malformed pointer: {value}"),
+        }
+    }
+}
+
+impl fmt::Display for GedcomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line() {
+            Some(line) => write!(f, "line {line}: {}", self.message()),
+            None => f.write_str(&self.message()),
+        }
+    }
+}
+
+impl std::error::Error for GedcomError {}
@@ -0,0 +1,270 @@
+//! Decompresses deflate data (RFC 1951), the compression zip archives use
+use std::convert::TryFrom;
+
+/// Length of a match for each length symbol from 257, before its extra bits
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+/// Distance of a match for each distance symbol, before its extra bits
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// The order the code lengths of the code length alphabet are stored in
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// The most bytes a byte of deflate data decompresses to, a 258 byte match taking 2 bits
+const MAX_RATIO: usize = 1032;
+
+/// Decompresses raw deflate data, as held by a zip entry, to the `size` bytes declared for it.
+/// Data decompressing to more or fewer bytes is rejected, stopping as soon as it has more.
+pub(crate) fn inflate(data: &[u8], size: usize) -> Result<Vec<u8>, &'static str> {
+    let mut reader = BitReader {
+        data,
+        position: 0,
+        buffer: 0,
+        count: 0,
+        size,
+    };
+    let mut output = Vec::with_capacity(size.min(data.len().saturating_mul(MAX_RATIO)));
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => reader.stored_block(&mut output)?,
+            1 => {
+                let (lengths, distances) = fixed_codes();
+                reader.compressed_block(&mut output, &lengths, &distances)?;
+            }
+            2 => {
+                let (lengths, distances) = reader.dynamic_codes()?;
+                reader.compressed_block(&mut output, &lengths, &distances)?;
+            }
+            _ => return Err("invalid block type"),
+        }
+        if last {
+            if output.len() < size {
+                return Err("fewer bytes than the declared size");
+            }
+            return Ok(output);
+        }
+    }
+}
+
+/// Canonical Huffman code, the number of codes of each bit length and the symbols in code order
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0; 16];
+        for &length in lengths {
+            counts[usize::from(length)] += 1;
+        }
+        counts[0] = 0;
+        let mut symbols = Vec::new();
+        for length in 1..16_u8 {
+            for (symbol, _) in (lengths.iter().enumerate()).filter(|(_, &l)| l == length) {
+                symbols.push(u16::try_from(symbol).unwrap_or_default());
+            }
+        }
+        Huffman { counts, symbols }
+    }
+
+    /// Checks that the code lengths give every bit sequence a symbol, as deflate requires of
+    /// its codes. Literal and distance codes may instead have a single 1-bit code or none, when
+    /// `single` is set.
+    fn check_complete(&self, single: bool) -> Result<(), &'static str> {
+        let mut left = 1_i32;
+        for &count in &self.counts[1..] {
+            left = (left << 1) - i32::from(count);
+            if left < 0 {
+                return Err("oversubscribed Huffman code");
+            }
+        }
+        let is_single = self.symbols.len() == usize::from(self.counts[1]) && self.counts[1] <= 1;
+        if left > 0 && !(single && is_single) {
+            return Err("incomplete Huffman code");
+        }
+        Ok(())
+    }
+}
+
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [8; 288];
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+/// Reads the bits of deflate data, least significant first
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    buffer: u32,
+    count: u32,
+    /// Number of bytes the data is declared to decompress to
+    size: usize,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, count: u32) -> Result<u32, &'static str> {
+        while self.count < count {
+            let byte = *self
+                .data
+                .get(self.position)
+                .ok_or("unexpected end of data")?;
+            self.position += 1;
+            self.buffer |= u32::from(byte) << self.count;
+            self.count += 8;
+        }
+        let value = self.buffer & ((1 << count) - 1);
+        self.buffer >>= count;
+        self.count -= count;
+        Ok(value)
+    }
+
+    /// Checks that `count` more bytes of output stay within the declared size
+    fn check_size(&self, output: &[u8], count: usize) -> Result<(), &'static str> {
+        if count > self.size - output.len() {
+            return Err("more bytes than the declared size");
+        }
+        Ok(())
+    }
+
+    fn stored_block(&mut self, output: &mut Vec<u8>) -> Result<(), &'static str> {
+        // stored blocks start at a byte boundary
+        self.buffer = 0;
+        self.count = 0;
+        let header =
+            (self.data.get(self.position..self.position + 4)).ok_or("unexpected end of data")?;
+        let length = usize::from(u16::from_le_bytes([header[0], header[1]]));
+        let complement = u16::from_le_bytes([header[2], header[3]]);
+        if length != usize::from(!complement) {
+            return Err("invalid stored block length");
+        }
+        self.position += 4;
+        self.check_size(output, length)?;
+        let bytes = (self.data.get(self.position..self.position + length))
+            .ok_or("unexpected end of data")?;
+        output.extend_from_slice(bytes);
+        self.position += length;
+        Ok(())
+    }
+
+    fn dynamic_codes(&mut self) -> Result<(Huffman, Huffman), &'static str> {
+        let length_count = usize::try_from(self.bits(5)?).unwrap_or_default() + 257;
+        let distance_count = usize::try_from(self.bits(5)?).unwrap_or_default() + 1;
+        let code_length_count = usize::try_from(self.bits(4)?).unwrap_or_default() + 4;
+        if length_count > 286 || distance_count > 30 {
+            return Err("too many length or distance codes");
+        }
+
+        let mut code_lengths = [0; 19];
+        for &index in &CODE_LENGTH_ORDER[..code_length_count] {
+            code_lengths[index] = u8::try_from(self.bits(3)?).unwrap_or_default();
+        }
+        let code_length_code = Huffman::new(&code_lengths);
+        code_length_code.check_complete(false)?;
+
+        let mut lengths = Vec::new();
+        while lengths.len() < length_count + distance_count {
+            let symbol = self.decode(&code_length_code)?;
+            let (length, repeat) = match symbol {
+                0..=15 => (u8::try_from(symbol).unwrap_or_default(), 1),
+                16 => {
+                    let previous = *lengths.last().ok_or("repeat without a length")?;
+                    (previous, 3 + self.bits(2)?)
+                }
+                17 => (0, 3 + self.bits(3)?),
+                _ => (0, 11 + self.bits(7)?),
+            };
+            for _ in 0..repeat {
+                lengths.push(length);
+            }
+        }
+        if lengths.len() > length_count + distance_count {
+            return Err("too many code lengths");
+        }
+        let (literal_lengths, distance_lengths) = lengths.split_at(length_count);
+        if literal_lengths[256] == 0 {
+            return Err("no end of block code");
+        }
+        let (literals, distances) = (
+            Huffman::new(literal_lengths),
+            Huffman::new(distance_lengths),
+        );
+        literals.check_complete(true)?;
+        distances.check_complete(true)?;
+        Ok((literals, distances))
+    }
+
+    fn compressed_block(
+        &mut self,
+        output: &mut Vec<u8>,
+        lengths: &Huffman,
+        distances: &Huffman,
+    ) -> Result<(), &'static str> {
+        loop {
+            let symbol = usize::from(self.decode(lengths)?);
+            match symbol {
+                0..=255 => {
+                    self.check_size(output, 1)?;
+                    output.push(u8::try_from(symbol).unwrap_or_default());
+                }
+                256 => return Ok(()),
+                _ => {
+                    let index = symbol - 257;
+                    let base = *LENGTH_BASE.get(index).ok_or("invalid length symbol")?;
+                    let length = usize::from(base)
+                        + usize::try_from(self.bits(u32::from(LENGTH_EXTRA[index]))?)
+                            .unwrap_or_default();
+                    let index = usize::from(self.decode(distances)?);
+                    let base = *DISTANCE_BASE.get(index).ok_or("invalid distance symbol")?;
+                    let distance = usize::from(base)
+                        + usize::try_from(self.bits(u32::from(DISTANCE_EXTRA[index]))?)
+                            .unwrap_or_default();
+                    if distance > output.len() {
+                        return Err("distance too far back");
+                    }
+                    self.check_size(output, length)?;
+                    let start = output.len() - distance;
+                    // a match may overlap the bytes it copies
+                    for offset in 0..length {
+                        output.push(output[start + offset]);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Decodes a symbol one bit at a time, codes being stored most significant bit first
+    fn decode(&mut self, huffman: &Huffman) -> Result<u16, &'static str> {
+        let mut code = 0;
+        let mut first = 0;
+        let mut index = 0;
+        for &count in &huffman.counts[1..] {
+            code |= self.bits(1)?;
+            let count = u32::from(count);
+            if code < first + count {
+                let symbol = usize::try_from(index + code - first).unwrap_or_default();
+                return Ok(huffman.symbols[symbol]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("invalid Huffman code")
+    }
+}
@@ -0,0 +1,307 @@
+//! Reads and writes GEDZIP packages, zip archives holding a GEDCOM 7.0 file named `gedcom.ged`
+//! along with the media files its `FILE` tags point to
+//!
+//! ```rust
+//! use gedcom::gedzip::{Gedzip, GedzipWriter};
+//!
+//! let package = Gedzip::open("./tests/fixtures/gedzip.gdz").unwrap();
+//! let mut data = package.parse().unwrap();
+//! for file in data.multimedia.iter().flat_map(|media| &media.files) {
+//!     if let Some(reference) = file.value.as_deref() {
+//!         let contents = package.resolve(reference).unwrap();
+//!     }
+//! }
+//!
+//! let mut writer = GedzipWriter::new();
+//! writer.write_data(&mut data, "./tests/fixtures").unwrap();
+//! let bytes = writer.finish().unwrap();
+//! ```
+mod inflate;
+mod zip;
+
+use std::borrow::Cow;
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::convert::{has_scheme, path_to_uri, uri_to_path};
+use crate::error::GedcomError;
+use crate::parser::Parser;
+use crate::tree::GedcomData;
+use crate::types::GedcomVersion;
+use crate::writer::{GedcomWriter, WriterOptions};
+use zip::{ArchivedEntry, Entry};
+
+/// Name of the GEDCOM file of a package
+pub const GEDCOM_ENTRY: &str = "gedcom.ged";
+
+/// Folder of a package the files pointed to by absolute paths are bundled in
+const MEDIA_FOLDER: &str = "media";
+
+/// A GEDZIP package read into memory
+///
+/// Entries stored without compression or compressed with deflate are read, which covers the
+/// archives zip tools write by default. Zip64 archives, which hold more than 4 GiB, are not.
+/// Entries are kept compressed and decompressed when asked for, never to more bytes than the
+/// archive declares for them.
+#[derive(Debug)]
+pub struct Gedzip {
+    archive: Vec<u8>,
+    entries: Vec<ArchivedEntry>,
+    /// The contents of `gedcom.ged`, decompressed when first asked for
+    gedcom: OnceCell<Vec<u8>>,
+}
+
+impl Gedzip {
+    /// Reads a package from the bytes of its archive
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GedcomError::InvalidArchive` if the bytes are not a zip archive that can be read
+    /// or it has no `gedcom.ged`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Gedzip, GedcomError> {
+        Gedzip::from_archive(bytes.to_vec())
+    }
+
+    fn from_archive(archive: Vec<u8>) -> Result<Gedzip, GedcomError> {
+        let entries = zip::read(&archive).map_err(invalid_archive)?;
+        if !entries.iter().any(|entry| entry.name == GEDCOM_ENTRY) {
+            return Err(invalid_archive(format!("no {GEDCOM_ENTRY}")));
+        }
+        Ok(Gedzip {
+            archive,
+            entries,
+            gedcom: OnceCell::new(),
+        })
+    }
+
+    /// Reads the package at the given path, see `Gedzip::from_bytes`
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GedcomError::File` if the file cannot be read, or the errors of `from_bytes`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Gedzip, GedcomError> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|error| file_error(path, &error))?;
+        Gedzip::from_archive(bytes)
+    }
+
+    /// The names of the files in the package, `gedcom.ged` among them
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|entry| entry.name.as_str())
+    }
+
+    /// The contents of the file with the given name, decompressed each time they are asked for.
+    /// `None` when the package has no such file.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GedcomError::InvalidArchive` if the file cannot be decompressed, or does not
+    /// match the size or checksum the archive declares for it.
+    pub fn entry(&self, name: &str) -> Result<Option<Cow<'_, [u8]>>, GedcomError> {
+        let Some(entry) = self.entries.iter().find(|entry| entry.name == name) else {
+            return Ok(None);
+        };
+        entry
+            .contents(&self.archive)
+            .map(Some)
+            .map_err(invalid_archive)
+    }
+
+    /// The bytes of the GEDCOM file, `gedcom.ged`, decompressed once
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GedcomError::InvalidArchive` if the file cannot be decompressed, see `entry`.
+    pub fn gedcom(&self) -> Result<&[u8], GedcomError> {
+        if let Some(contents) = self.gedcom.get() {
+            return Ok(contents);
+        }
+        let contents = self.entry(GEDCOM_ENTRY)?.unwrap_or_default().into_owned();
+        Ok(self.gedcom.get_or_init(|| contents))
+    }
+
+    /// A parser of the GEDCOM file, to set its `ParserOptions` before parsing
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GedcomError` if the GEDCOM file cannot be decompressed or decoded, see
+    /// `Parser::from_bytes`.
+    pub fn parser(&self) -> Result<Parser<'_>, GedcomError> {
+        Parser::from_bytes(self.gedcom()?)
+    }
+
+    /// Parses the GEDCOM file
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GedcomError` if the GEDCOM file cannot be parsed.
+    pub fn parse(&self) -> Result<GedcomData, GedcomError> {
+        self.parser()?.parse_record()
+    }
+
+    /// The contents of the file the value of a `FILE` points to, a URI relative to the root of
+    /// the package. `None` for URLs, absolute paths and files missing from the package.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GedcomError::InvalidArchive` if the file cannot be decompressed, see `entry`.
+    pub fn resolve(&self, reference: &str) -> Result<Option<Cow<'_, [u8]>>, GedcomError> {
+        entry_name(reference).map_or(Ok(None), |name| self.entry(&name))
+    }
+}
+
+/// Writes a GEDZIP package, storing its files without compression
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Default)]
+pub struct GedzipWriter {
+    entries: Vec<Entry>,
+}
+
+impl GedzipWriter {
+    /// Creates a writer of an empty package
+    #[must_use]
+    pub fn new() -> GedzipWriter {
+        GedzipWriter::default()
+    }
+
+    /// Writes the data as the GEDCOM 7.0 text of `gedcom.ged`, along with the local files its
+    /// multimedia records point to. Data read from a 5.5.1 file should be converted with
+    /// `convert::convert` first.
+    ///
+    /// Relative references are read from the `media_root` folder and bundled at the same path.
+    /// Files pointed to by an absolute path or a `file` URI, or outside of the folder, are
+    /// bundled in a `media` folder and their `FILE` changed to point there. URLs are left as
+    /// they are.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GedcomError::File` if a file cannot be read.
+    pub fn write_data(
+        &mut self,
+        data: &mut GedcomData,
+        media_root: impl AsRef<Path>,
+    ) -> Result<(), GedcomError> {
+        let media_root = media_root.as_ref();
+        let mut bundled = HashMap::new();
+        for media in &mut data.multimedia {
+            for file in &mut media.files {
+                let translations = file.translations.iter_mut();
+                let references = (file.value.iter_mut())
+                    .chain(translations.filter_map(|translation| translation.value.as_mut()));
+                for reference in references {
+                    self.bundle(reference, media_root, &mut bundled)?;
+                }
+            }
+        }
+
+        let mut writer = GedcomWriter::with_options(WriterOptions {
+            version: GedcomVersion::V7,
+            ..WriterOptions::default()
+        });
+        writer.write_data(data);
+        let contents = writer.finish()?;
+        let name = GEDCOM_ENTRY.to_string();
+        self.entries.retain(|entry| entry.name != name);
+        self.entries.insert(0, Entry { name, contents });
+        Ok(())
+    }
+
+    /// Adds a file to the package, replacing any file with the same name
+    pub fn add_file(&mut self, name: &str, contents: Vec<u8>) {
+        let name = name.to_string();
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.name == name) {
+            entry.contents = contents;
+        } else {
+            self.entries.push(Entry { name, contents });
+        }
+    }
+
+    /// The bytes of the zip archive
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GedcomError::InvalidArchive` if the package holds more than a zip archive
+    /// without zip64 can, 65535 files or 4 GiB.
+    pub fn finish(self) -> Result<Vec<u8>, GedcomError> {
+        zip::write(&self.entries).map_err(invalid_archive)
+    }
+
+    /// Adds the local file a reference points to, changing the reference to the entry when it
+    /// is not already one. `bundled` holds the entries of the paths bundled so far.
+    fn bundle(
+        &mut self,
+        reference: &mut String,
+        media_root: &Path,
+        bundled: &mut HashMap<PathBuf, String>,
+    ) -> Result<(), GedcomError> {
+        if has_scheme(reference) && !reference.starts_with("file:") {
+            return Ok(());
+        }
+        let path = media_root.join(uri_to_path(reference));
+        if let Some(name) = bundled.get(&path) {
+            *reference = path_to_uri(name);
+            return Ok(());
+        }
+        let contents = fs::read(&path).map_err(|error| file_error(&path, &error))?;
+
+        let name = match entry_name(reference) {
+            Some(name) if self.entry_is_free(&name) => name,
+            _ => {
+                let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                let mut name = format!("{MEDIA_FOLDER}/{file_name}");
+                let mut number = 1;
+                while !self.entry_is_free(&name) {
+                    number += 1;
+                    name = format!("{MEDIA_FOLDER}/{number}-{file_name}");
+                }
+                name
+            }
+        };
+        *reference = path_to_uri(&name);
+        self.entries.push(Entry {
+            name: name.clone(),
+            contents,
+        });
+        bundled.insert(path, name);
+        Ok(())
+    }
+
+    fn entry_is_free(&self, name: &str) -> bool {
+        name != GEDCOM_ENTRY && !self.entries.iter().any(|entry| entry.name == name)
+    }
+}
+
+/// The name of the entry a relative reference points to, without `.` segments. `None` for
+/// URIs, absolute paths and references leaving the root of the package.
+fn entry_name(reference: &str) -> Option<String> {
+    if has_scheme(reference) || reference.starts_with('/') || reference.starts_with('\\') {
+        return None;
+    }
+    let path = uri_to_path(reference).replace('\\', "/");
+    let mut segments = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => (),
+            ".." => return None,
+            _ => segments.push(segment),
+        }
+    }
+    if segments.is_empty() {
+        None
+    } else {
+        Some(segments.join("/"))
+    }
+}
+
+fn invalid_archive(message: String) -> GedcomError {
+    GedcomError::InvalidArchive { message }
+}
+
+fn file_error(path: &Path, error: &std::io::Error) -> GedcomError {
+    GedcomError::File {
+        path: path.to_path_buf(),
+        message: error.to_string(),
+    }
+}
@@ -0,0 +1,210 @@
+//! Reads and writes the entries of zip archives, stored or compressed with deflate
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::ops::Range;
+
+use super::inflate::inflate;
+
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4b50;
+/// Version 2.0 of the format, the first with deflate and folders
+const VERSION: u16 = 20;
+/// Flag marking the names of entries as UTF-8
+const UTF8_NAMES: u16 = 1 << 11;
+/// The MS-DOS date of 1 January 1980, the earliest a zip archive can hold
+const EPOCH_DATE: u16 = 0x21;
+
+/// A file to write to an archive
+#[derive(Clone, Debug)]
+pub(crate) struct Entry {
+    pub(crate) name: String,
+    pub(crate) contents: Vec<u8>,
+}
+
+/// A file of an archive being read, its data left compressed in the bytes of the archive
+#[derive(Clone, Debug)]
+pub(crate) struct ArchivedEntry {
+    pub(crate) name: String,
+    method: u16,
+    crc: u32,
+    /// Size of the contents once decompressed
+    size: usize,
+    /// Byte range of the data in the archive
+    data: Range<usize>,
+}
+
+impl ArchivedEntry {
+    /// The contents of the entry, decompressing its data from the bytes of the archive
+    pub(crate) fn contents<'b>(&self, archive: &'b [u8]) -> Result<Cow<'b, [u8]>, String> {
+        let data = (archive.get(self.data.clone())).ok_or("truncated archive")?;
+        let contents = match self.method {
+            0 => Cow::Borrowed(data),
+            _ => Cow::Owned(
+                inflate(data, self.size).map_err(|message| format!("{}: {message}", self.name))?,
+            ),
+        };
+        if crc32(&contents) != self.crc {
+            return Err(format!("checksum mismatch of {}", self.name));
+        }
+        Ok(contents)
+    }
+}
+
+/// Reads the files of an archive from its central directory, skipping folders. Their data is
+/// left compressed until `ArchivedEntry::contents` is called.
+pub(crate) fn read(bytes: &[u8]) -> Result<Vec<ArchivedEntry>, String> {
+    // the end of central directory record is followed by a comment of at most 65535 bytes
+    let end = (0..=bytes.len().saturating_sub(22))
+        .rev()
+        .take(65536)
+        .find(|&offset| read_u32(bytes, offset) == Some(END_OF_CENTRAL_DIRECTORY))
+        .ok_or("no end of central directory")?;
+    let count = read_u16(bytes, end + 10).ok_or("truncated archive")?;
+    let mut offset = read_offset(bytes, end + 16)?;
+
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        if read_u32(bytes, offset) != Some(CENTRAL_HEADER) {
+            return Err("invalid central directory".to_string());
+        }
+        let field = |at: usize| read_u16(bytes, offset + at).ok_or("truncated archive");
+        let method = field(10)?;
+        let name_length = usize::from(field(28)?);
+        let extra_length = usize::from(field(30)?);
+        let comment_length = usize::from(field(32)?);
+        let crc = read_u32(bytes, offset + 16).ok_or("truncated archive")?;
+        let compressed_size = read_offset(bytes, offset + 20)?;
+        let size = read_offset(bytes, offset + 24)?;
+        let local_offset = read_offset(bytes, offset + 42)?;
+        let name =
+            (bytes.get(offset + 46..offset + 46 + name_length)).ok_or("truncated archive")?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        offset += 46 + name_length + extra_length + comment_length;
+        if name.ends_with('/') {
+            continue;
+        }
+
+        if read_u32(bytes, local_offset) != Some(LOCAL_HEADER) {
+            return Err(format!("invalid local header of {name}"));
+        }
+        let local_field = |at: usize| read_u16(bytes, local_offset + at).ok_or("truncated archive");
+        let start =
+            local_offset + 30 + usize::from(local_field(26)?) + usize::from(local_field(28)?);
+        let data = start..start + compressed_size;
+        if bytes.get(data.clone()).is_none() {
+            return Err(format!("truncated entry {name}"));
+        }
+        match method {
+            0 if compressed_size != size => return Err(format!("size mismatch of {name}")),
+            0 | 8 => (),
+            _ => return Err(format!("unsupported compression method {method} of {name}")),
+        }
+        entries.push(ArchivedEntry {
+            name,
+            method,
+            crc,
+            size,
+            data,
+        });
+    }
+    Ok(entries)
+}
+
+/// Writes an archive storing the entries without compression
+pub(crate) fn write(entries: &[Entry]) -> Result<Vec<u8>, String> {
+    let too_large = |_| "archive too large, only zip archives below 4 GiB are written".to_string();
+    let mut archive = Vec::new();
+    let mut directory = Vec::new();
+    for entry in entries {
+        let offset = u32::try_from(archive.len()).map_err(too_large)?;
+        let size = u32::try_from(entry.contents.len()).map_err(too_large)?;
+        let name_length = u16::try_from(entry.name.len()).map_err(|_| "entry name too long")?;
+        let crc = crc32(&entry.contents);
+
+        push_u32(&mut archive, LOCAL_HEADER);
+        // version needed, flags, method, time and date
+        for field in [VERSION, UTF8_NAMES, 0, 0, EPOCH_DATE] {
+            push_u16(&mut archive, field);
+        }
+        for field in [crc, size, size] {
+            push_u32(&mut archive, field);
+        }
+        push_u16(&mut archive, name_length);
+        push_u16(&mut archive, 0);
+        archive.extend_from_slice(entry.name.as_bytes());
+        archive.extend_from_slice(&entry.contents);
+
+        push_u32(&mut directory, CENTRAL_HEADER);
+        // version made by, version needed, flags, method, time and date
+        for field in [VERSION, VERSION, UTF8_NAMES, 0, 0, EPOCH_DATE] {
+            push_u16(&mut directory, field);
+        }
+        for field in [crc, size, size] {
+            push_u32(&mut directory, field);
+        }
+        // name, extra field and comment lengths, disk number and internal attributes
+        for field in [name_length, 0, 0, 0, 0] {
+            push_u16(&mut directory, field);
+        }
+        // external attributes
+        push_u32(&mut directory, 0);
+        push_u32(&mut directory, offset);
+        directory.extend_from_slice(entry.name.as_bytes());
+    }
+
+    let count = u16::try_from(entries.len()).map_err(|_| "too many entries")?;
+    let directory_offset = u32::try_from(archive.len()).map_err(too_large)?;
+    let directory_size = u32::try_from(directory.len()).map_err(too_large)?;
+    archive.append(&mut directory);
+    push_u32(&mut archive, END_OF_CENTRAL_DIRECTORY);
+    // disk numbers, then the entry count of this disk and of the archive
+    for field in [0, 0, count, count] {
+        push_u16(&mut archive, field);
+    }
+    push_u32(&mut archive, directory_size);
+    push_u32(&mut archive, directory_offset);
+    // comment length
+    push_u16(&mut archive, 0);
+    Ok(archive)
+}
+
+/// The CRC-32 checksum of the bytes, as zip archives and PNG compute it
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    let field = bytes.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([field[0], field[1]]))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let field = bytes.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([field[0], field[1], field[2], field[3]]))
+}
+
+/// Reads an offset or size, which zip64 archives set to `0xFFFFFFFF` and write elsewhere
+fn read_offset(bytes: &[u8], offset: usize) -> Result<usize, String> {
+    match read_u32(bytes, offset) {
+        Some(u32::MAX) => Err("zip64 archives are not supported".to_string()),
+        Some(value) => usize::try_from(value).map_err(|_| "archive too large".to_string()),
+        None => Err("truncated archive".to_string()),
+    }
+}
+
+fn push_u16(bytes: &mut Vec<u8>, value: u16) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn push_u32(bytes: &mut Vec<u8>, value: u32) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
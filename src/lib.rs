@@ -3,11 +3,11 @@
 //! ```rust
 //! use gedcom::parser::Parser;
 //!
-//! // the parser takes the gedcom file contents as a chars iterator
+//! // the parser takes the gedcom file contents as a string slice
 //! let gedcom_source = std::fs::read_to_string("./tests/fixtures/sample.ged").unwrap();
 //!
-//! let mut parser = Parser::new(gedcom_source.chars());
-//! let gedcom_data = parser.parse_record();
+//! let mut parser = Parser::new(&gedcom_source);
+//! let gedcom_data = parser.parse_record().unwrap();
 //!
 //! // output some stats on the gedcom contents
 //! gedcom_data.stats();
@@ -19,9 +19,28 @@
 #[macro_use]
 mod util;
 
+mod error;
+pub use error::GedcomError;
+
+mod diagnostic;
+pub use diagnostic::{Diagnostic, Severity};
+
+pub mod convert;
+pub mod encoding;
+pub mod gedzip;
 pub mod parser;
 pub mod tokenizer;
 pub mod types;
+pub mod writer;
 
 mod tree;
 pub use tree::GedcomData;
+
+mod record;
+pub use record::Record;
+
+mod node;
+pub use node::GedcomNode;
+
+mod span;
+pub use span::{Position, Span};
@@ -0,0 +1,81 @@
+//! The untyped tree of lines every record is read into
+use crate::span::Span;
+
+/// A single GEDCOM line along with all of the lines nested beneath it
+///
+/// Every record is read into a tree of nodes before the typed model is built from it, see
+/// `Parser::parse_tree`. Typed records keep their node to give access to structures they do not
+/// model, and user-defined tags that start with an underscore (`_UID`, `_MARNM`, ...) are held
+/// as nodes directly.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GedcomNode {
+    /// The depth of the line within the tree
+    pub level: u8,
+    /// The `optional_xref_ID` of the line
+    pub xref: Option<String>,
+    /// The tag of the line
+    pub tag: String,
+    /// The `optional_line_value` of the line
+    pub value: Option<String>,
+    /// Whether the value is a pointer to a record, rather than text that reads like one once its
+    /// `@@` are unescaped
+    pub pointer: bool,
+    /// The lines nested beneath this one
+    pub children: Vec<GedcomNode>,
+    /// The line number of the line within the file
+    pub line: u32,
+    /// The text of the line and every line nested beneath it, without the last terminator
+    pub span: Span,
+    /// The exact text of the line when parsed with `ParserOptions::lossless`, from its level
+    /// through its terminator along with any blank lines and indentation up to the next level.
+    /// `GedcomWriter::write_tree` writes it in place of the line.
+    pub raw: Option<String>,
+}
+
+impl GedcomNode {
+    /// The first child with the given tag
+    #[must_use]
+    pub fn child(&self, tag: &str) -> Option<&GedcomNode> {
+        self.children.iter().find(|child| child.tag == tag)
+    }
+
+    /// The value joined with its `CONT` and `CONC` continuation lines, `CONT` starting a new line
+    /// and `CONC` continuing the current one. `None` without a value or continuation lines.
+    #[must_use]
+    pub fn text(&self) -> Option<String> {
+        let mut text = self.value.clone();
+        for child in &self.children {
+            let separator = match child.tag.as_str() {
+                "CONT" => "\n",
+                "CONC" => "",
+                _ => continue,
+            };
+            let text = text.get_or_insert_with(String::new);
+            text.push_str(separator);
+            text.push_str(child.value.as_deref().unwrap_or_default());
+        }
+        text
+    }
+
+    /// Joins the `CONT` and `CONC` continuation lines into the value, see `text`
+    pub(crate) fn join_continuation_lines(&mut self) {
+        if !self.children.iter().any(GedcomNode::is_continuation) {
+            return;
+        }
+        self.value = self.text();
+        self.children.retain(|child| !child.is_continuation());
+    }
+
+    /// Whether the line continues the value of its parent, a `CONT` or `CONC`
+    #[must_use]
+    pub fn is_continuation(&self) -> bool {
+        matches!(self.tag.as_str(), "CONT" | "CONC")
+    }
+
+    /// Whether the tag is user-defined, which the specification requires to start with an
+    /// underscore
+    #[must_use]
+    pub fn is_extension(&self) -> bool {
+        self.tag.starts_with('_')
+    }
+}
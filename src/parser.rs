@@ -1,481 +1,1471 @@
-//! The state machine that parses a char iterator of the gedcom's contents
-use std::{panic, str::Chars};
+//! The state machine that parses the gedcom's contents
+use std::convert::TryFrom;
+use std::io::BufRead;
 
-use crate::tokenizer::{Token, Tokenizer};
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::encoding::{self, FileEncoding};
+use crate::error::GedcomError;
+use crate::tokenizer::{self, Token, Tokenizer};
 use crate::tree::GedcomData;
 use crate::types::{
-    Address, Event, Family, FamilyLink, Gender, Individual, Name, RepoCitation, Repository, Source,
-    SourceCitation, Submitter,
+    Address, ChangeDate, Corporation, Crop, Encoding, Event, EventType, ExternalId, Family,
+    FamilyLink, GedcomMeta, GedcomVersion, Gender, HeadDate, HeadPlace, HeadSource, HeadSourceData,
+    Header, Individual, LdsOrdinance, LdsOrdinanceType, Media, MediaFile, MediaLink, Name,
+    NonEvent, Note, NoteRef, RepoCitation, Repository, SchemaTag, Source, SourceCitation,
+    Submission, Submitter, Translation, UserReference,
 };
+use crate::{GedcomNode, Record};
+
+/// How the parser reacts to tags and values it cannot handle
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Strictness {
+    /// Parsing fails with a `GedcomError` at the first problem
+    #[default]
+    Strict,
+    /// Problems are skipped along with their substructures and recorded as `Diagnostic`s
+    Lenient,
+}
+
+/// Options that control how a file is parsed
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ParserOptions {
+    /// How to react to tags and values that cannot be handled
+    pub strictness: Strictness,
+    /// Number of threads `parse_record` parses level-0 records across, 0 and 1 parsing on the
+    /// calling thread. Only contents held in full are parsed in parallel, not a reader.
+    pub threads: usize,
+    /// Keeps `CONT` and `CONC` continuation lines as children of the `GedcomNode` they continue,
+    /// to write the text back split as it was. They are joined into its value otherwise. The
+    /// typed records hold the joined text either way.
+    pub keep_line_splits: bool,
+    /// Keeps the exact text of every line in the `raw` of its `GedcomNode`, along with the
+    /// continuation lines, so that `GedcomWriter::write_tree` writes the file back as it was
+    /// read. Only the tree of `Parser::parse_tree` round-trips byte for byte:
+    /// `GedcomWriter::write_data` writes typed records from their fields, in the normalized
+    /// form of the writer options, and ignores the nodes they keep.
+    pub lossless: bool,
+}
 
 /// The Gedcom parser that converts the token list into a data structure
+///
+/// Each level-0 record is first read into an untyped `GedcomNode` tree, which the typed
+/// records of `GedcomData` are then built from.
 pub struct Parser<'a> {
     tokenizer: Tokenizer<'a>,
+    options: ParserOptions,
+    diagnostics: Vec<Diagnostic>,
+    version: GedcomVersion,
+    encoding: FileEncoding,
 }
 
 impl<'a> Parser<'a> {
-    /// Creates a parser state machine for parsing a gedcom file as a chars iterator
+    /// Creates a parser state machine for parsing the contents of a gedcom file
+    #[must_use]
+    pub fn new(contents: &'a str) -> Parser<'a> {
+        Parser::with_options(contents, ParserOptions::default())
+    }
+
+    /// Creates a parser state machine with the given `ParserOptions`
+    #[must_use]
+    pub fn with_options(contents: &'a str, options: ParserOptions) -> Parser<'a> {
+        let mut parser = Parser::new_with_tokenizer(Tokenizer::new(contents));
+        parser.set_options(options);
+        parser
+    }
+
+    /// Creates a parser state machine for the raw bytes of a gedcom file, decoding them
+    /// according to their byte order mark or the `CHAR` tag of their header. See
+    /// `encoding::decode`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GedcomError` if the bytes cannot be decoded.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Parser<'a>, GedcomError> {
+        let tokenizer = Tokenizer::new(encoding::decode(bytes)?);
+        let mut parser = Parser::new_with_tokenizer(tokenizer);
+        parser.encoding = encoding::detect(bytes);
+        Ok(parser)
+    }
+
+    /// Creates a parser state machine reading a gedcom file one line at a time, decoding it like
+    /// `Parser::from_bytes` does. Only the lines of the record being parsed are held in memory.
+    #[must_use]
+    pub fn from_reader(reader: impl BufRead + 'a) -> Parser<'a> {
+        Parser::new_with_tokenizer(Tokenizer::from_reader(reader))
+    }
+
+    fn new_with_tokenizer(tokenizer: Tokenizer<'a>) -> Parser<'a> {
+        Parser {
+            tokenizer,
+            options: ParserOptions::default(),
+            diagnostics: Vec::new(),
+            version: GedcomVersion::default(),
+            encoding: FileEncoding::default(),
+        }
+    }
+
+    /// Sets the `ParserOptions` of a parser that has not started parsing
+    pub fn set_options(&mut self, options: ParserOptions) {
+        self.options = options;
+        if options.lossless {
+            self.tokenizer.keep_raw();
+        }
+    }
+
+    /// The version of the specification the file follows, read from the `GEDC.VERS` of its
+    /// header. GEDCOM 5 until the header has been parsed.
+    #[must_use]
+    pub fn version(&self) -> GedcomVersion {
+        self.version
+    }
+
+    /// How the bytes of the file encode its text, its byte order mark and byte order included, to
+    /// write it back the same way with `WriterOptions::encoding`. UTF-8 for contents given as
+    /// text, whose byte order mark is part of the text, and for a reader until the encoding is
+    /// detected or declared.
     #[must_use]
-    pub fn new(chars: Chars<'a>) -> Parser {
-        let mut tokenizer = Tokenizer::new(chars);
-        tokenizer.next_token();
-        Parser { tokenizer }
+    pub fn encoding(&self) -> FileEncoding {
+        self.tokenizer.reader_encoding().unwrap_or(self.encoding)
+    }
+
+    /// Sets the version the lines that follow are read with. GEDCOM 7.0 only escapes an `@`
+    /// starting a value and no longer allows `CONC`.
+    fn set_version(&mut self, version: GedcomVersion) {
+        self.version = version;
+        self.tokenizer
+            .unescape_leading_only(version == GedcomVersion::V7);
+    }
+
+    /// Problems skipped over while parsing.
+    /// Strict parsing only skips unknown top-level records and reports malformed pointers,
+    /// lenient parsing skips any problem.
+    #[must_use]
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
     }
 
     /// Does the actual parsing of the record.
-    pub fn parse_record(&mut self) -> GedcomData {
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GedcomError` describing the first line that could not be parsed.
+    pub fn parse_record(&mut self) -> Result<GedcomData, GedcomError> {
+        if self.options.threads > 1 {
+            if let Some((contents, offset)) = self.tokenizer.untokenized() {
+                let (result, diagnostics) = parse_parallel(contents, offset, self.options);
+                self.diagnostics.extend(diagnostics);
+                self.tokenizer.finish();
+                return result;
+            }
+        }
+
         let mut data = GedcomData::default();
+        for record in self.records() {
+            data.add_record(record?);
+        }
+        Ok(data)
+    }
+
+    /// Iterates over the typed records of the file, each one being parsed as it is reached.
+    /// Only the lines of the record being parsed are held in memory when reading from a reader.
+    ///
+    /// Ends after the `TRLR` or the first `GedcomError`. Records with unknown tags are skipped.
+    pub fn records(&mut self) -> Records<'_, 'a> {
+        Records {
+            parser: self,
+            done: false,
+        }
+    }
+
+    /// Parses the file into untyped nodes without building the typed model, one `GedcomNode`
+    /// per level-0 record including the `HEAD` and `TRLR`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GedcomError` describing the first line that could not be tokenized.
+    pub fn parse_tree(&mut self) -> Result<Vec<GedcomNode>, GedcomError> {
+        let mut nodes = Vec::new();
+        while let Some(node) = self.next_node()? {
+            nodes.push(node);
+        }
+        Ok(nodes)
+    }
+
+    /// Loads the next token, lenient parsing skips lines without a valid level
+    fn next_token(&mut self) -> Result<(), GedcomError> {
         loop {
-            let level = match self.tokenizer.current_token {
-                Token::Level(n) => n,
-                _ => panic!(
-                    "{} Expected Level, found {:?}",
-                    self.dbg(),
-                    self.tokenizer.current_token
-                ),
-            };
+            match self.tokenizer.next_token() {
+                Err(error @ GedcomError::InvalidLevel { .. }) => {
+                    self.recover_line(error, 0)?;
+                    self.tokenizer.skip_line();
+                }
+                result => return result,
+            }
+        }
+    }
 
-            self.tokenizer.next_token();
-
-            let mut pointer: Option<String> = None;
-            if let Token::Pointer(xref) = &self.tokenizer.current_token {
-                pointer = Some(xref.to_string());
-                self.tokenizer.next_token();
-            }
-
-            if let Token::Tag(tag) = &self.tokenizer.current_token {
-                match tag.as_str() {
-                    "FAM" => data.add_family(self.parse_family(level, pointer)),
-                    "HEAD" => self.parse_header(),
-                    "INDI" => data.add_individual(self.parse_individual(level, pointer)),
-                    "REPO" => data.add_repository(self.parse_repository(level, pointer)),
-                    "SOUR" => data.add_source(self.parse_source(level, pointer)),
-                    "SUBM" => data.add_submitter(self.parse_submitter(level, pointer)),
-                    "TRLR" => break,
-                    _ => {
-                        println!("{} Unhandled tag {}", self.dbg(), tag);
-                        self.tokenizer.next_token();
+    /// Reads the next level-0 record, or `None` at the end of the file
+    fn next_node(&mut self) -> Result<Option<GedcomNode>, GedcomError> {
+        if self.tokenizer.current_token() == Token::None {
+            self.next_token()?;
+        }
+        loop {
+            match self.tokenizer.current_token() {
+                Token::Level(level) => {
+                    let Some(node) = self.read_node(level)? else {
+                        continue;
+                    };
+                    if node.tag == "HEAD" {
+                        self.set_version(header_version(&node));
                     }
-                };
-            } else {
-                println!(
-                    "{} Unhandled token {:?}",
-                    self.dbg(),
-                    self.tokenizer.current_token
-                );
-                self.tokenizer.next_token();
+                    return Ok(Some(node));
+                }
+                Token::EOF => return Ok(None),
+                _ => return Err(self.unexpected_token("Record")),
+            }
+        }
+    }
+
+    /// Reads the line starting at the current level token and every line nested beneath it.
+    /// `None` when lenient parsing skips the line for having no tag.
+    fn read_node(&mut self, level: u8) -> Result<Option<GedcomNode>, GedcomError> {
+        let line = self.tokenizer.line;
+        let start = self.tokenizer.current_span();
+        self.next_token()?;
+
+        let mut xref: Option<String> = None;
+        if let Token::Pointer(pointer) = self.tokenizer.current_token() {
+            xref = Some(pointer.into_owned());
+            self.next_token()?;
+        }
+
+        let Token::Tag(tag) = self.tokenizer.current_token() else {
+            // the line ended without a tag, the token is already that of the next line
+            let error = GedcomError::UnexpectedToken {
+                line,
+                record: "Line",
+                token: format!("{:?}", self.tokenizer.current_token()),
             };
+            self.recover_line(error, level)?;
+            return Ok(None);
+        };
+        let tag = tag.into_owned();
+        let mut span = start.to(self.tokenizer.current_span());
+        self.next_token()?;
+
+        let mut value: Option<String> = None;
+        if let Token::Escape(escape) = self.tokenizer.current_token() {
+            // escapes are kept as they are, only tokenized apart from the text
+            value = Some(escape.into_owned());
+            span = start.to(self.tokenizer.current_span());
+            self.next_token()?;
+        }
+        let mut pointer = false;
+        match self.tokenizer.current_token() {
+            Token::LineValue(val) => {
+                value = Some(match value {
+                    Some(escape) => format!("{escape} {val}"),
+                    None => val.into_owned(),
+                });
+                span = start.to(self.tokenizer.current_span());
+                self.next_token()?;
+            }
+            Token::Pointer(val) => {
+                value = Some(val.into_owned());
+                pointer = true;
+                span = start.to(self.tokenizer.current_span());
+                self.next_token()?;
+            }
+            _ => (),
+        }
+
+        // the next line has been reached, ending the text of this one
+        let raw = self.options.lossless.then(|| self.tokenizer.take_raw());
+        let mut node = GedcomNode {
+            level,
+            xref,
+            tag,
+            value,
+            pointer,
+            children: Vec::new(),
+            line,
+            span,
+            raw,
+        };
+        while let Token::Level(cur_level) = self.tokenizer.current_token() {
+            if cur_level <= level {
+                break;
+            }
+            if cur_level > level + 1 {
+                // lenient parsing keeps the line beneath the one it skips a level from
+                let error = GedcomError::InvalidLevel {
+                    line: self.tokenizer.line,
+                    value: cur_level.to_string(),
+                };
+                self.recover_line(error, cur_level)?;
+            }
+            if let Some(child) = self.read_node(cur_level)? {
+                node.children.push(child);
+            }
         }
+        if self.version == GedcomVersion::V7 {
+            if let Some(conc) = node.children.iter().find(|child| child.tag == "CONC") {
+                let error = Parser::unexpected_tag("Line", conc);
+                self.recover(error, Severity::Warning, conc)?;
+            }
+        }
+        if !self.options.keep_line_splits && !self.options.lossless {
+            node.join_continuation_lines();
+        }
+        if let Some(last) = node.children.last() {
+            node.span = node.span.to(last.span);
+        }
+
+        Ok(Some(node))
+    }
 
-        data
+    /// Builds the typed record of a level-0 node, or `None` if its tag is unknown
+    fn build_record(&mut self, node: GedcomNode) -> Result<Option<Record>, GedcomError> {
+        if let Some(xref) = &node.xref {
+            self.check_pointer(xref, &node);
+        }
+        let record = match node.tag.as_str() {
+            "FAM" => Record::Family(self.parse_family(node)?),
+            "HEAD" => Record::Header(Box::new(self.parse_header(node)?)),
+            "INDI" => Record::Individual(self.parse_individual(node)?),
+            "NOTE" | "SNOTE" => Record::Note(self.parse_note(node)?),
+            "OBJE" => Record::Media(self.parse_media(node)?),
+            "REPO" => Record::Repository(self.parse_repository(node)?),
+            "SOUR" => Record::Source(self.parse_source(node)?),
+            "SUBM" => Record::Submitter(self.parse_submitter(node)?),
+            "SUBN" => Record::Submission(self.parse_submission(node)?),
+            "TRLR" => Record::Trailer,
+            _ if node.is_extension() => Record::Extension(node),
+            _ => {
+                // unknown records are skipped regardless of strictness
+                let error = Parser::unexpected_tag("Record", &node);
+                self.report(&error, Severity::Warning, &node);
+                return Ok(None);
+            }
+        };
+        Ok(Some(record))
     }
 
-    fn parse_header(&mut self) {
-        // just skipping the header for now
-        while self.tokenizer.current_token != Token::Level(0) {
-            self.tokenizer.next_token();
+    /// Builds the next known record, or `None` at the end of the file
+    fn next_record(&mut self) -> Result<Option<Record>, GedcomError> {
+        while let Some(node) = self.next_node()? {
+            if let Some(record) = self.build_record(node)? {
+                return Ok(Some(record));
+            }
         }
-        println!("  skipping header");
+        Ok(None)
     }
 
-    fn parse_submitter(&mut self, level: u8, xref: Option<String>) -> Submitter {
-        // skip over SUBM tag name
-        self.tokenizer.next_token();
+    fn parse_header(&mut self, node: GedcomNode) -> Result<Header, GedcomError> {
+        let mut header = Header::default();
 
-        let mut submitter = Submitter::new(xref);
-        while self.tokenizer.current_token != Token::Level(level) {
-            match &self.tokenizer.current_token {
-                Token::Tag(tag) => match tag.as_str() {
-                    "NAME" => submitter.name = Some(self.take_line_value()),
-                    "ADDR" => {
-                        submitter.address = Some(self.parse_address(level + 1));
-                    }
-                    "PHON" => submitter.phone = Some(self.take_line_value()),
-                    _ => panic!("{} Unhandled Submitter Tag: {}", self.dbg(), tag),
-                },
-                Token::Level(_) => self.tokenizer.next_token(),
-                _ => panic!(
-                    "Unhandled Submitter Token: {:?}",
-                    self.tokenizer.current_token
-                ),
-            }
-        }
-        // println!("found submitter:\n{:#?}", submitter);
-        submitter
-    }
-
-    fn parse_individual(&mut self, level: u8, xref: Option<String>) -> Individual {
-        // skip over INDI tag name
-        self.tokenizer.next_token();
-        let mut individual = Individual::new(xref);
-
-        while self.tokenizer.current_token != Token::Level(level) {
-            match &self.tokenizer.current_token {
-                Token::Tag(tag) => match tag.as_str() {
-                    "NAME" => {
-                        individual.name = Some(self.parse_name(level + 1));
-                    }
-                    "SEX" => {
-                        individual.sex = self.parse_gender();
-                    }
-                    "ADOP" | "BIRT" | "BURI" | "DEAT" | "RESI" => {
-                        let tag_clone = tag.clone();
-                        individual.add_event(self.parse_event(tag_clone.as_str(), level + 1));
+        for child in &node.children {
+            match child.tag.as_str() {
+                "GEDC" => header.gedcom = Some(self.parse_gedcom_meta(child)?),
+                "CHAR" => header.encoding = Some(self.parse_encoding(child)?),
+                "SOUR" => header.source = Some(self.parse_head_source(child)?),
+                "DEST" => header.destination = Some(self.take_line_value(child)?),
+                "DATE" => header.date = Some(self.parse_head_date(child)?),
+                "SUBM" => header.submitter_tag = Some(self.take_pointer(child)?),
+                "SUBN" => header.submission_tag = Some(self.take_pointer(child)?),
+                "FILE" => header.filename = Some(self.take_line_value(child)?),
+                "COPR" => header.copyright = Some(self.take_continued_text(child, "Header")?),
+                "LANG" => header.language = Some(self.take_line_value(child)?),
+                "PLAC" => header.place = Some(self.parse_head_place(child)?),
+                "NOTE" => header.note = Some(self.take_continued_text(child, "Header")?),
+                "SCHMA" => header.schema = self.parse_schema(child)?,
+                _ if child.is_extension() => header.extensions.push(child.clone()),
+                _ => self.skip_unhandled_tag("Header", child)?,
+            }
+        }
+
+        // GEDCOM 7.0 files are always UTF-8
+        if let Some(charset) = node.child("CHAR") {
+            if header.version() == GedcomVersion::V7 && charset.value.as_deref() != Some("UTF-8") {
+                let error = Parser::invalid_value("Header", charset);
+                self.recover(error, Severity::Error, charset)?;
+            }
+        }
+
+        header.node = Some(node);
+        Ok(header)
+    }
+
+    fn parse_schema(&mut self, node: &GedcomNode) -> Result<Vec<SchemaTag>, GedcomError> {
+        let mut schema = Vec::new();
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "TAG" => {
+                    let value = self.take_line_value(child)?;
+                    if let Some((tag, uri)) = value.split_once(' ') {
+                        schema.push(SchemaTag {
+                            tag: tag.to_string(),
+                            uri: uri.trim().to_string(),
+                        });
+                    } else {
+                        let error = Parser::invalid_value("Schema", child);
+                        self.recover(error, Severity::Error, child)?;
                     }
-                    "FAMC" | "FAMS" => {
-                        let tag_copy = tag.clone();
-                        individual.add_family(self.parse_family_link(tag_copy.as_str(), level + 1));
+                }
+                _ => self.skip_unhandled_tag("Schema", child)?,
+            }
+        }
+
+        Ok(schema)
+    }
+
+    fn parse_gedcom_meta(&mut self, node: &GedcomNode) -> Result<GedcomMeta, GedcomError> {
+        let mut gedcom = GedcomMeta::default();
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "VERS" => gedcom.version = Some(self.take_line_value(child)?),
+                "FORM" => {
+                    gedcom.form = Some(self.take_line_value(child)?);
+                    // 5.5.5 files nest the version of the form beneath it
+                    for form_child in &child.children {
+                        match form_child.tag.as_str() {
+                            "VERS" => gedcom.form_version = Some(self.take_line_value(form_child)?),
+                            _ => self.skip_unhandled_tag("GedcomMeta", form_child)?,
+                        }
                     }
-                    _ => panic!("{} Unhandled Individual Tag: {}", self.dbg(), tag),
-                },
-                Token::Level(_) => self.tokenizer.next_token(),
-                _ => panic!(
-                    "Unhandled Individual Token: {:?}",
-                    self.tokenizer.current_token
-                ),
+                }
+                _ => self.skip_unhandled_tag("GedcomMeta", child)?,
             }
         }
-        // println!("found individual:\n{:#?}", individual);
-        individual
+
+        Ok(gedcom)
     }
 
-    fn parse_family(&mut self, level: u8, xref: Option<String>) -> Family {
-        // skip over FAM tag name
-        self.tokenizer.next_token();
-        let mut family = Family::new(xref);
+    fn parse_encoding(&mut self, node: &GedcomNode) -> Result<Encoding, GedcomError> {
+        let mut encoding = Encoding {
+            value: Some(self.take_line_value(node)?),
+            version: None,
+        };
 
-        while self.tokenizer.current_token != Token::Level(level) {
-            match &self.tokenizer.current_token {
-                Token::Tag(tag) => match tag.as_str() {
-                    "MARR" => family.add_event(self.parse_event("MARR", level + 1)),
-                    "HUSB" => family.set_individual1(self.take_line_value()),
-                    "WIFE" => family.set_individual2(self.take_line_value()),
-                    "CHIL" => family.add_child(self.take_line_value()),
-                    _ => panic!("{} Unhandled Family Tag: {}", self.dbg(), tag),
-                },
-                Token::Level(_) => self.tokenizer.next_token(),
-                _ => panic!("Unhandled Family Token: {:?}", self.tokenizer.current_token),
+        for child in &node.children {
+            match child.tag.as_str() {
+                "VERS" => encoding.version = Some(self.take_line_value(child)?),
+                _ => self.skip_unhandled_tag("Encoding", child)?,
             }
         }
 
-        // println!("found family:\n{:#?}", family);
-        family
+        Ok(encoding)
     }
 
-    fn parse_source(&mut self, level: u8, xref: Option<String>) -> Source {
-        // skip SOUR tag
-        self.tokenizer.next_token();
-        let mut source = Source::new(xref);
+    fn parse_head_source(&mut self, node: &GedcomNode) -> Result<HeadSource, GedcomError> {
+        let mut source = HeadSource {
+            value: Some(self.take_line_value(node)?),
+            ..HeadSource::default()
+        };
 
-        loop {
-            if let Token::Level(cur_level) = self.tokenizer.current_token {
-                if cur_level <= level {
-                    break;
-                }
+        for child in &node.children {
+            match child.tag.as_str() {
+                "VERS" => source.version = Some(self.take_line_value(child)?),
+                "NAME" => source.name = Some(self.take_line_value(child)?),
+                "CORP" => source.corporation = Some(self.parse_corporation(child)?),
+                "DATA" => source.data = Some(self.parse_head_source_data(child)?),
+                _ => self.skip_unhandled_tag("HeadSource", child)?,
             }
-            match &self.tokenizer.current_token {
-                Token::Tag(tag) => match tag.as_str() {
-                    "DATA" => self.tokenizer.next_token(),
-                    "EVEN" => {
-                        let events_recorded = self.take_line_value();
-                        let mut event = self.parse_event("OTHER", level + 2);
-                        event.with_source_data(events_recorded);
-                        source.data.add_event(event);
-                    }
-                    "AGNC" => source.data.agency = Some(self.take_line_value()),
-                    "ABBR" => source.abbreviation = Some(self.take_continued_text(level + 1)),
-                    "TITL" => source.title = Some(self.take_continued_text(level + 1)),
-                    "REPO" => source.add_repo_citation(self.parse_repo_citation(level + 1)),
-                    _ => panic!("{} Unhandled Source Tag: {}", self.dbg(), tag),
-                },
-                Token::Level(_) => self.tokenizer.next_token(),
-                _ => panic!("Unhandled Source Token: {:?}", self.tokenizer.current_token),
+        }
+
+        Ok(source)
+    }
+
+    fn parse_corporation(&mut self, node: &GedcomNode) -> Result<Corporation, GedcomError> {
+        let mut corporation = Corporation {
+            value: node.value.clone(),
+            ..Corporation::default()
+        };
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "ADDR" => corporation.address = Some(self.parse_address(child)?),
+                "PHON" => corporation.phone = Some(self.take_line_value(child)?),
+                "EMAIL" => corporation.email = Some(self.take_line_value(child)?),
+                "FAX" => corporation.fax = Some(self.take_line_value(child)?),
+                "WWW" => corporation.website = Some(self.take_line_value(child)?),
+                _ => self.skip_unhandled_tag("Corporation", child)?,
             }
         }
 
-        // println!("found source:\n{:#?}", source);
-        source
+        Ok(corporation)
     }
 
-    fn parse_repository(&mut self, level: u8, xref: Option<String>) -> Repository {
-        // skip REPO tag
-        self.tokenizer.next_token();
-        let mut repo = Repository {
-            xref,
-            name: None,
-            address: None,
+    fn parse_head_source_data(&mut self, node: &GedcomNode) -> Result<HeadSourceData, GedcomError> {
+        let mut data = HeadSourceData {
+            value: node.value.clone(),
+            ..HeadSourceData::default()
         };
-        loop {
-            if let Token::Level(cur_level) = self.tokenizer.current_token {
-                if cur_level <= level {
-                    break;
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "DATE" => data.date = Some(self.take_line_value(child)?),
+                "COPR" => {
+                    data.copyright = Some(self.take_continued_text(child, "HeadSourceData")?);
                 }
+                _ => self.skip_unhandled_tag("HeadSourceData", child)?,
+            }
+        }
+
+        Ok(data)
+    }
+
+    fn parse_head_date(&mut self, node: &GedcomNode) -> Result<HeadDate, GedcomError> {
+        let mut date = HeadDate {
+            value: Some(self.take_line_value(node)?),
+            time: None,
+        };
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "TIME" => date.time = Some(self.take_line_value(child)?),
+                _ => self.skip_unhandled_tag("HeadDate", child)?,
             }
-            match &self.tokenizer.current_token {
-                Token::Tag(tag) => match tag.as_str() {
-                    "NAME" => repo.name = Some(self.take_line_value()),
-                    "ADDR" => repo.address = Some(self.parse_address(level + 1)),
-                    _ => panic!("{} Unhandled Repository Tag: {}", self.dbg(), tag),
-                },
-                Token::Level(_) => self.tokenizer.next_token(),
-                _ => panic!(
-                    "Unhandled Repository Token: {:?}",
-                    self.tokenizer.current_token
-                ),
+        }
+
+        Ok(date)
+    }
+
+    fn parse_head_place(&mut self, node: &GedcomNode) -> Result<HeadPlace, GedcomError> {
+        let mut place = HeadPlace::default();
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "FORM" => place.form = Some(self.take_line_value(child)?),
+                _ => self.skip_unhandled_tag("HeadPlace", child)?,
             }
         }
-        // println!("found repositiory:\n{:#?}", repo);
-        repo
+
+        Ok(place)
     }
 
-    fn parse_family_link(&mut self, tag: &str, level: u8) -> FamilyLink {
-        let xref = self.take_line_value();
-        let mut link = FamilyLink::new(xref, tag);
+    fn parse_submitter(&mut self, node: GedcomNode) -> Result<Submitter, GedcomError> {
+        let mut submitter = Submitter::new(node.xref.clone());
 
-        loop {
-            if let Token::Level(cur_level) = self.tokenizer.current_token {
-                if cur_level <= level {
-                    break;
+        for child in &node.children {
+            match child.tag.as_str() {
+                "NAME" => submitter.name = Some(self.take_line_value(child)?),
+                "ADDR" => submitter.address = Some(self.parse_address(child)?),
+                "PHON" => submitter.phone = Some(self.take_line_value(child)?),
+                "NOTE" | "SNOTE" => submitter.notes.push(self.parse_note_ref(child)?),
+                "EXID" => submitter.external_ids.push(self.parse_external_id(child)?),
+                "UID" => submitter.unique_ids.push(self.take_line_value(child)?),
+                "RFN" => submitter.record_file_number = Some(self.take_line_value(child)?),
+                _ if child.is_extension() => submitter.extensions.push(child.clone()),
+                _ => self.skip_unhandled_tag("Submitter", child)?,
+            }
+        }
+
+        submitter.node = Some(node);
+        Ok(submitter)
+    }
+
+    fn parse_submission(&mut self, node: GedcomNode) -> Result<Submission, GedcomError> {
+        let mut submission = Submission::new(node.xref.clone());
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "SUBM" => submission.submitter = Some(self.take_pointer(child)?),
+                "FAMF" => submission.family_file = Some(self.take_line_value(child)?),
+                "TEMP" => submission.temple_code = Some(self.take_line_value(child)?),
+                "ANCE" => submission.ancestor_generations = Some(self.take_line_value(child)?),
+                "DESC" => submission.descendant_generations = Some(self.take_line_value(child)?),
+                "ORDI" => submission.ordinance_process_flag = Some(self.take_line_value(child)?),
+                "RIN" => submission.automated_record_id = Some(self.take_line_value(child)?),
+                "NOTE" | "SNOTE" => submission.notes.push(self.parse_note_ref(child)?),
+                "CHAN" => submission.change_date = Some(self.parse_change_date(child)?),
+                _ if child.is_extension() => submission.extensions.push(child.clone()),
+                _ => self.skip_unhandled_tag("Submission", child)?,
+            }
+        }
+
+        submission.node = Some(node);
+        Ok(submission)
+    }
+
+    fn parse_individual(&mut self, node: GedcomNode) -> Result<Individual, GedcomError> {
+        let mut individual = Individual::new(node.xref.clone());
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "NAME" => individual.name = Some(self.parse_name(child)?),
+                "SEX" => individual.sex = self.parse_gender(child)?,
+                "ADOP" | "BAPM" | "BARM" | "BASM" | "BIRT" | "BLES" | "BURI" | "CENS" | "CHR"
+                | "CHRA" | "CONF" | "CREM" | "DEAT" | "EMIG" | "EVEN" | "FCOM" | "GRAD"
+                | "IMMI" | "NATU" | "ORDN" | "PROB" | "RESI" | "RETI" | "WILL" => {
+                    individual.add_event(self.parse_event(&child.tag, child, false)?);
+                }
+                "NO" => individual.non_events.push(self.parse_non_event(child)?),
+                "BAPL" | "CONL" | "ENDL" | "INIL" | "SLGC" => {
+                    let ordinance = self.parse_lds_ordinance(child)?;
+                    individual.lds_ordinances.push(ordinance);
+                }
+                "FAMC" | "FAMS" => individual.add_family(self.parse_family_link(child)?),
+                "NOTE" | "SNOTE" => individual.add_note(self.parse_note_ref(child)?),
+                "OBJE" => individual.multimedia.push(self.parse_media_link(child)?),
+                "EXID" => individual.external_ids.push(self.parse_external_id(child)?),
+                "UID" => individual.unique_ids.push(self.take_line_value(child)?),
+                "RFN" => individual.record_file_number = Some(self.take_line_value(child)?),
+                _ if child.is_extension() => individual.extensions.push(child.clone()),
+                _ => self.skip_unhandled_tag("Individual", child)?,
+            }
+        }
+
+        individual.node = Some(node);
+        Ok(individual)
+    }
+
+    fn parse_family(&mut self, node: GedcomNode) -> Result<Family, GedcomError> {
+        let mut family = Family::new(node.xref.clone());
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "ANUL" | "CENS" | "DIV" | "DIVF" | "ENGA" | "EVEN" | "MARB" | "MARC" | "MARL"
+                | "MARR" | "MARS" | "RESI" => {
+                    family.add_event(self.parse_event(&child.tag, child, true)?);
+                }
+                "NO" => family.non_events.push(self.parse_non_event(child)?),
+                "SLGS" => family.lds_ordinances.push(self.parse_lds_ordinance(child)?),
+                "HUSB" | "WIFE" => {
+                    let xref = self.take_pointer(child)?;
+                    let is_set = if child.tag == "HUSB" {
+                        family.set_individual1(xref)
+                    } else {
+                        family.set_individual2(xref)
+                    };
+                    if !is_set {
+                        let error = Parser::unexpected_tag("Family", child);
+                        self.recover(error, Severity::Error, child)?;
+                    }
+                }
+                "CHIL" => family.add_child(self.take_pointer(child)?),
+                "NCHI" => {
+                    if let Ok(num_children) = self.take_line_value(child)?.parse() {
+                        family.num_children = Some(num_children);
+                    } else {
+                        let error = Parser::invalid_value("Family", child);
+                        self.recover(error, Severity::Error, child)?;
+                    }
                 }
+                "NOTE" | "SNOTE" => family.add_note(self.parse_note_ref(child)?),
+                "OBJE" => family.multimedia.push(self.parse_media_link(child)?),
+                "EXID" => family.external_ids.push(self.parse_external_id(child)?),
+                "UID" => family.unique_ids.push(self.take_line_value(child)?),
+                _ if child.is_extension() => family.extensions.push(child.clone()),
+                _ => self.skip_unhandled_tag("Family", child)?,
             }
-            match &self.tokenizer.current_token {
-                Token::Tag(tag) => match tag.as_str() {
-                    "PEDI" => link.set_pedigree(self.take_line_value().as_str()),
-                    _ => panic!("{} Unhandled FamilyLink Tag: {}", self.dbg(), tag),
-                },
-                Token::Level(_) => self.tokenizer.next_token(),
-                _ => panic!(
-                    "Unhandled FamilyLink Token: {:?}",
-                    self.tokenizer.current_token
-                ),
+        }
+
+        family.node = Some(node);
+        Ok(family)
+    }
+
+    fn parse_source(&mut self, node: GedcomNode) -> Result<Source, GedcomError> {
+        let mut source = Source::new(node.xref.clone());
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "DATA" => {
+                    for data in &child.children {
+                        match data.tag.as_str() {
+                            "EVEN" => {
+                                let events_recorded = self.take_line_value(data)?;
+                                let mut event = self.parse_event("OTHER", data, false)?;
+                                event.with_source_data(events_recorded);
+                                source.data.add_event(event);
+                            }
+                            "AGNC" => source.data.agency = Some(self.take_line_value(data)?),
+                            "NOTE" | "SNOTE" => source.notes.push(self.parse_note_ref(data)?),
+                            _ => self.skip_unhandled_tag("SourceData", data)?,
+                        }
+                    }
+                }
+                "ABBR" => source.abbreviation = Some(self.take_continued_text(child, "Source")?),
+                "TITL" => source.title = Some(self.take_continued_text(child, "Source")?),
+                "REPO" => source.add_repo_citation(self.parse_repo_citation(child)?),
+                "NOTE" | "SNOTE" => source.notes.push(self.parse_note_ref(child)?),
+                "OBJE" => source.multimedia.push(self.parse_media_link(child)?),
+                "EXID" => source.external_ids.push(self.parse_external_id(child)?),
+                "UID" => source.unique_ids.push(self.take_line_value(child)?),
+                _ if child.is_extension() => source.extensions.push(child.clone()),
+                _ => self.skip_unhandled_tag("Source", child)?,
+            }
+        }
+
+        source.node = Some(node);
+        Ok(source)
+    }
+
+    fn parse_repository(&mut self, node: GedcomNode) -> Result<Repository, GedcomError> {
+        let mut repo = Repository::new(node.xref.clone());
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "NAME" => repo.name = Some(self.take_line_value(child)?),
+                "ADDR" => repo.address = Some(self.parse_address(child)?),
+                "NOTE" | "SNOTE" => repo.notes.push(self.parse_note_ref(child)?),
+                "EXID" => repo.external_ids.push(self.parse_external_id(child)?),
+                "UID" => repo.unique_ids.push(self.take_line_value(child)?),
+                _ if child.is_extension() => repo.extensions.push(child.clone()),
+                _ => self.skip_unhandled_tag("Repository", child)?,
             }
         }
 
-        link
+        repo.node = Some(node);
+        Ok(repo)
     }
 
-    fn parse_repo_citation(&mut self, level: u8) -> RepoCitation {
-        let xref = self.take_line_value();
+    fn parse_note(&mut self, node: GedcomNode) -> Result<Note, GedcomError> {
+        let mut shared_note = Note::new(node.xref.clone());
+        shared_note.value = Some(node.text().unwrap_or_default());
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "SOUR" => shared_note.add_citation(self.parse_citation(child)?),
+                "MIME" => shared_note.mime = Some(self.take_line_value(child)?),
+                "LANG" => shared_note.language = Some(self.take_line_value(child)?),
+                "TRAN" => shared_note
+                    .translations
+                    .push(self.parse_translation(child)?),
+                "EXID" => shared_note
+                    .external_ids
+                    .push(self.parse_external_id(child)?),
+                "UID" => shared_note.unique_ids.push(self.take_line_value(child)?),
+                "REFN" => shared_note
+                    .user_references
+                    .push(self.parse_user_reference(child)?),
+                "RIN" => shared_note.automated_record_id = Some(self.take_line_value(child)?),
+                "CHAN" => shared_note.change_date = Some(self.parse_change_date(child)?),
+                _ if child.is_extension() => shared_note.extensions.push(child.clone()),
+                _ => self.skip_unhandled_tag("Note", child)?,
+            }
+        }
+
+        shared_note.node = Some(node);
+        Ok(shared_note)
+    }
+
+    fn parse_media(&mut self, node: GedcomNode) -> Result<Media, GedcomError> {
+        let mut media = Media::new(node.xref.clone());
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "FILE" => media.files.push(self.parse_media_file(child)?),
+                "REFN" => media
+                    .user_references
+                    .push(self.parse_user_reference(child)?),
+                "RIN" => media.automated_record_id = Some(self.take_line_value(child)?),
+                "EXID" => media.external_ids.push(self.parse_external_id(child)?),
+                "UID" => media.unique_ids.push(self.take_line_value(child)?),
+                "NOTE" | "SNOTE" => media.notes.push(self.parse_note_ref(child)?),
+                "SOUR" => media.citations.push(self.parse_citation(child)?),
+                "CHAN" => media.change_date = Some(self.parse_change_date(child)?),
+                _ if child.is_extension() => media.extensions.push(child.clone()),
+                _ => self.skip_unhandled_tag("Media", child)?,
+            }
+        }
+
+        media.node = Some(node);
+        Ok(media)
+    }
+
+    fn parse_family_link(&mut self, node: &GedcomNode) -> Result<FamilyLink, GedcomError> {
+        let xref = self.take_pointer(node)?;
+        let Some(mut link) = FamilyLink::new(xref, &node.tag) else {
+            return Err(Parser::unexpected_tag("FamilyLink", node));
+        };
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "PEDI" => {
+                    let pedigree = self.take_line_value(child)?;
+                    if !link.set_pedigree(pedigree.as_str()) {
+                        let error = Parser::invalid_value("FamilyLink", child);
+                        self.recover(error, Severity::Error, child)?;
+                    }
+                }
+                _ if child.is_extension() => link.3.push(child.clone()),
+                _ => self.skip_unhandled_tag("FamilyLink", child)?,
+            }
+        }
+
+        Ok(link)
+    }
+
+    fn parse_repo_citation(&mut self, node: &GedcomNode) -> Result<RepoCitation, GedcomError> {
         let mut citation = RepoCitation {
-            xref,
+            xref: self.take_pointer(node)?,
             call_number: None,
+            extensions: Vec::new(),
         };
-        loop {
-            if let Token::Level(cur_level) = self.tokenizer.current_token {
-                if cur_level <= level {
-                    break;
-                }
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "CALN" => citation.call_number = Some(self.take_line_value(child)?),
+                _ if child.is_extension() => citation.extensions.push(child.clone()),
+                _ => self.skip_unhandled_tag("RepoCitation", child)?,
             }
-            match &self.tokenizer.current_token {
-                Token::Tag(tag) => match tag.as_str() {
-                    "CALN" => citation.call_number = Some(self.take_line_value()),
-                    _ => panic!("{} Unhandled RepoCitation Tag: {}", self.dbg(), tag),
-                },
-                Token::Level(_) => self.tokenizer.next_token(),
-                _ => panic!(
-                    "Unhandled RepoCitation Token: {:?}",
-                    self.tokenizer.current_token
-                ),
-            }
-        }
-        citation
-    }
-
-    fn parse_gender(&mut self) -> Gender {
-        self.tokenizer.next_token();
-        let gender: Gender;
-        if let Token::LineValue(gender_string) = &self.tokenizer.current_token {
-            gender = match gender_string.as_str() {
-                "M" => Gender::Male,
-                "F" => Gender::Female,
-                "N" => Gender::Nonbinary,
-                "U" => Gender::Unknown,
-                _ => panic!("{} Unknown gender value {}", self.dbg(), gender_string),
-            };
-        } else {
-            panic!(
-                "Expected gender LineValue, found {:?}",
-                self.tokenizer.current_token
-            );
         }
-        self.tokenizer.next_token();
-        gender
+
+        Ok(citation)
+    }
+
+    fn parse_gender(&mut self, node: &GedcomNode) -> Result<Gender, GedcomError> {
+        let gender = match self.take_line_value(node)?.as_str() {
+            "M" => Gender::Male,
+            "F" => Gender::Female,
+            "N" => Gender::Nonbinary,
+            "X" if self.version == GedcomVersion::V7 => Gender::Nonbinary,
+            "U" => Gender::Unknown,
+            _ => {
+                let error = Parser::invalid_value("Individual", node);
+                self.recover(error, Severity::Error, node)?;
+                Gender::Unknown
+            }
+        };
+        Ok(gender)
     }
 
-    fn parse_name(&mut self, level: u8) -> Name {
+    fn parse_name(&mut self, node: &GedcomNode) -> Result<Name, GedcomError> {
         let mut name = Name {
-            value: Some(self.take_line_value()),
+            value: Some(self.take_line_value(node)?),
             given: None,
             surname: None,
+            extensions: Vec::new(),
         };
 
-        loop {
-            if let Token::Level(cur_level) = self.tokenizer.current_token {
-                if cur_level <= level {
-                    break;
-                }
+        for child in &node.children {
+            match child.tag.as_str() {
+                "GIVN" => name.given = Some(self.take_line_value(child)?),
+                "SURN" => name.surname = Some(self.take_line_value(child)?),
+                _ if child.is_extension() => name.extensions.push(child.clone()),
+                _ => self.skip_unhandled_tag("Name", child)?,
             }
-            match &self.tokenizer.current_token {
-                Token::Tag(tag) => match tag.as_str() {
-                    "GIVN" => name.given = Some(self.take_line_value()),
-                    "SURN" => name.surname = Some(self.take_line_value()),
-                    _ => panic!("{} Unhandled Name Tag: {}", self.dbg(), tag),
-                },
-                Token::Level(_) => self.tokenizer.next_token(),
-                _ => panic!("Unhandled Name Token: {:?}", self.tokenizer.current_token),
+        }
+
+        Ok(name)
+    }
+
+    /// Parses an event with the given tag, family events also record the ages of the spouses
+    fn parse_event(
+        &mut self,
+        tag: &str,
+        node: &GedcomNode,
+        is_family_event: bool,
+    ) -> Result<Event, GedcomError> {
+        let Some(mut event) = Event::from_tag(tag) else {
+            return Err(Parser::unexpected_tag("Event", node));
+        };
+        event.value = node.text();
+        event.span = Some(node.span);
+        event.node = Some(node.clone());
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "TYPE" => event.event_type = Some(self.take_line_value(child)?),
+                "DATE" => {
+                    event.date = self.take_date(child)?;
+                    event.date_phrase = Parser::take_phrase(child);
+                }
+                "SDATE" => event.sort_date = Some(self.take_line_value(child)?),
+                "PLAC" => event.place = Some(self.take_line_value(child)?),
+                "SOUR" => event.add_citation(self.parse_citation(child)?),
+                "NOTE" | "SNOTE" => event.notes.push(self.parse_note_ref(child)?),
+                "OBJE" => event.multimedia.push(self.parse_media_link(child)?),
+                "HUSB" if is_family_event => event.husband_age = self.parse_spouse_age(child)?,
+                "WIFE" if is_family_event => event.wife_age = self.parse_spouse_age(child)?,
+                "FAMC" if matches!(tag, "ADOP" | "BIRT" | "CHR") => {
+                    self.parse_event_family(&mut event, child)?;
+                }
+                _ if child.is_extension() => event.extensions.push(child.clone()),
+                _ => self.skip_unhandled_tag("Event", child)?,
             }
         }
 
-        name
+        Ok(event)
     }
 
-    fn parse_event(&mut self, tag: &str, level: u8) -> Event {
-        self.tokenizer.next_token();
-        let mut event = Event::from_tag(tag);
-        loop {
-            if let Token::Level(cur_level) = self.tokenizer.current_token {
-                if cur_level <= level {
-                    break;
+    /// Parses an event asserted not to have happened, whose value is the tag of the event
+    fn parse_non_event(&mut self, node: &GedcomNode) -> Result<NonEvent, GedcomError> {
+        let tag = self.take_line_value(node)?;
+        let Some(event) = Event::from_tag(&tag) else {
+            return Err(Parser::invalid_value("NonEvent", node));
+        };
+        let mut non_event = NonEvent {
+            event: event.event,
+            date: None,
+            date_phrase: None,
+            citations: Vec::new(),
+            notes: Vec::new(),
+        };
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "DATE" => {
+                    non_event.date = self.take_date(child)?;
+                    non_event.date_phrase = Parser::take_phrase(child);
                 }
+                "SOUR" => non_event.citations.push(self.parse_citation(child)?),
+                "NOTE" | "SNOTE" => non_event.notes.push(self.parse_note_ref(child)?),
+                _ => self.skip_unhandled_tag("NonEvent", child)?,
             }
-            match &self.tokenizer.current_token {
-                Token::Tag(tag) => match tag.as_str() {
-                    "DATE" => event.date = Some(self.take_line_value()),
-                    "PLAC" => event.place = Some(self.take_line_value()),
-                    "SOUR" => event.add_citation(self.parse_citation(level + 1)),
-                    _ => panic!("{} Unhandled Event Tag: {}", self.dbg(), tag),
-                },
-                Token::Level(_) => self.tokenizer.next_token(),
-                _ => panic!("Unhandled Event Token: {:?}", self.tokenizer.current_token),
+        }
+
+        Ok(non_event)
+    }
+
+    fn parse_lds_ordinance(&mut self, node: &GedcomNode) -> Result<LdsOrdinance, GedcomError> {
+        let Some(ordinance) = LdsOrdinanceType::from_tag(&node.tag) else {
+            return Err(Parser::unexpected_tag("LdsOrdinance", node));
+        };
+        let mut ordinance = LdsOrdinance::new(ordinance);
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "DATE" => ordinance.date = Some(self.take_line_value(child)?),
+                "TEMP" => ordinance.temple = Some(self.take_line_value(child)?),
+                "PLAC" => ordinance.place = Some(self.take_line_value(child)?),
+                "STAT" => {
+                    ordinance.status = Some(self.take_line_value(child)?);
+                    ordinance.status_date = child.child("DATE").and_then(GedcomNode::text);
+                }
+                "FAMC" if ordinance.ordinance == LdsOrdinanceType::ChildSealing => {
+                    ordinance.family = Some(self.take_pointer(child)?);
+                }
+                "SOUR" => ordinance.citations.push(self.parse_citation(child)?),
+                "NOTE" | "SNOTE" => ordinance.notes.push(self.parse_note_ref(child)?),
+                _ => self.skip_unhandled_tag("LdsOrdinance", child)?,
             }
         }
-        event
+
+        Ok(ordinance)
     }
 
-    fn parse_address(&mut self, level: u8) -> Address {
-        // skip ADDR tag
-        self.tokenizer.next_token();
-        let mut address = Address::default();
-        let mut value = String::new();
+    /// Parses the `AGE` of the husband or wife at the time of a family event
+    fn parse_spouse_age(&mut self, node: &GedcomNode) -> Result<Option<String>, GedcomError> {
+        let mut age = None;
 
-        // handle value on ADDR line
-        if let Token::LineValue(addr) = &self.tokenizer.current_token {
-            value.push_str(addr);
-            self.tokenizer.next_token();
+        for child in &node.children {
+            match child.tag.as_str() {
+                "AGE" => age = Some(self.take_line_value(child)?),
+                _ => self.skip_unhandled_tag("Event", child)?,
+            }
         }
 
-        loop {
-            if let Token::Level(cur_level) = self.tokenizer.current_token {
-                if cur_level <= level {
-                    break;
+        Ok(age)
+    }
+
+    /// Parses the family of a birth, christening or adoption event, which also names the
+    /// adopting parents of an adoption
+    fn parse_event_family(
+        &mut self,
+        event: &mut Event,
+        node: &GedcomNode,
+    ) -> Result<(), GedcomError> {
+        event.family = Some(self.take_pointer(node)?);
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "ADOP" if event.event == EventType::Adoption => {
+                    let adopted_by = self.take_line_value(child)?;
+                    if !event.set_adopted_by(&adopted_by) {
+                        let error = Parser::invalid_value("Event", child);
+                        self.recover(error, Severity::Error, child)?;
+                    }
                 }
+                _ => self.skip_unhandled_tag("Event", child)?,
             }
-            match &self.tokenizer.current_token {
-                Token::Tag(tag) => match tag.as_str() {
-                    "CONT" => {
-                        value.push('\n');
-                        value.push_str(&self.take_line_value());
-                    }
-                    "ADR1" => address.adr1 = Some(self.take_line_value()),
-                    "ADR2" => address.adr2 = Some(self.take_line_value()),
-                    "ADR3" => address.adr3 = Some(self.take_line_value()),
-                    "CITY" => address.city = Some(self.take_line_value()),
-                    "STAE" => address.state = Some(self.take_line_value()),
-                    "POST" => address.post = Some(self.take_line_value()),
-                    "CTRY" => address.country = Some(self.take_line_value()),
-                    _ => panic!("{} Unhandled Address Tag: {}", self.dbg(), tag),
-                },
-                Token::Level(_) => self.tokenizer.next_token(),
-                _ => panic!(
-                    "Unhandled Address Token: {:?}",
-                    self.tokenizer.current_token
-                ),
-            }
-        }
-
-        if &value != "" {
+        }
+
+        Ok(())
+    }
+
+    fn parse_address(&mut self, node: &GedcomNode) -> Result<Address, GedcomError> {
+        let mut address = Address::default();
+        let value = node.text().unwrap_or_default();
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "ADR1" => address.adr1 = Some(self.take_line_value(child)?),
+                "ADR2" => address.adr2 = Some(self.take_line_value(child)?),
+                "ADR3" => address.adr3 = Some(self.take_line_value(child)?),
+                "CITY" => address.city = Some(self.take_line_value(child)?),
+                "STAE" => address.state = Some(self.take_line_value(child)?),
+                "POST" => address.post = Some(self.take_line_value(child)?),
+                "CTRY" => address.country = Some(self.take_line_value(child)?),
+                _ => self.skip_unhandled_tag("Address", child)?,
+            }
+        }
+
+        if !value.is_empty() {
             address.value = Some(value);
         }
 
-        address
+        Ok(address)
     }
 
-    fn parse_citation(&mut self, level: u8) -> SourceCitation {
+    fn parse_citation(&mut self, node: &GedcomNode) -> Result<SourceCitation, GedcomError> {
+        let xref = self.take_line_value(node)?;
+        if xref.starts_with('@') {
+            // citations without a source record describe it in text instead
+            self.check_pointer(&xref, node);
+        }
         let mut citation = SourceCitation {
-            xref: self.take_line_value(),
+            xref,
             page: None,
+            notes: Vec::new(),
+            multimedia: Vec::new(),
+            extensions: Vec::new(),
         };
-        loop {
-            if let Token::Level(cur_level) = self.tokenizer.current_token {
-                if cur_level <= level {
-                    break;
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "PAGE" => citation.page = Some(self.take_line_value(child)?),
+                "NOTE" | "SNOTE" => citation.notes.push(self.parse_note_ref(child)?),
+                "OBJE" => citation.multimedia.push(self.parse_media_link(child)?),
+                _ if child.is_extension() => citation.extensions.push(child.clone()),
+                _ => self.skip_unhandled_tag("Citation", child)?,
+            }
+        }
+
+        Ok(citation)
+    }
+
+    /// Parses a note, which GEDCOM 7.0 writes in place with `NOTE` and points to with `SNOTE`
+    fn parse_note_ref(&mut self, node: &GedcomNode) -> Result<NoteRef, GedcomError> {
+        if node.tag == "SNOTE" {
+            return Ok(NoteRef::Pointer(self.take_pointer(node)?));
+        }
+        if node.pointer && self.version == GedcomVersion::V5 {
+            return Ok(NoteRef::Pointer(self.take_pointer(node)?));
+        }
+        Ok(NoteRef::Text(self.take_continued_text(node, "Note")?))
+    }
+
+    fn parse_external_id(&mut self, node: &GedcomNode) -> Result<ExternalId, GedcomError> {
+        let mut external_id = ExternalId {
+            value: Some(self.take_line_value(node)?),
+            id_type: None,
+        };
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "TYPE" => external_id.id_type = Some(self.take_line_value(child)?),
+                _ => self.skip_unhandled_tag("ExternalId", child)?,
+            }
+        }
+
+        Ok(external_id)
+    }
+
+    fn parse_translation(&mut self, node: &GedcomNode) -> Result<Translation, GedcomError> {
+        let mut translation = Translation {
+            value: Some(self.take_line_value(node)?),
+            ..Translation::default()
+        };
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "LANG" => translation.language = Some(self.take_line_value(child)?),
+                "MIME" | "FORM" => translation.mime = Some(self.take_line_value(child)?),
+                _ => self.skip_unhandled_tag("Translation", child)?,
+            }
+        }
+
+        Ok(translation)
+    }
+
+    fn parse_media_link(&mut self, node: &GedcomNode) -> Result<MediaLink, GedcomError> {
+        if let Some(xref) = &node.value {
+            self.check_pointer(xref, node);
+            if node.children.is_empty() {
+                return Ok(MediaLink::Pointer(xref.clone()));
+            }
+            let mut crop = None;
+            let mut title = None;
+            for child in &node.children {
+                match child.tag.as_str() {
+                    "CROP" => crop = Some(self.parse_crop(child)?),
+                    "TITL" => title = Some(self.take_line_value(child)?),
+                    _ => self.skip_unhandled_tag("MediaLink", child)?,
                 }
             }
-            match &self.tokenizer.current_token {
-                Token::Tag(tag) => match tag.as_str() {
-                    "PAGE" => citation.page = Some(self.take_line_value()),
-                    _ => panic!("{} Unhandled Citation Tag: {}", self.dbg(), tag),
-                },
-                Token::Level(_) => self.tokenizer.next_token(),
-                _ => panic!(
-                    "Unhandled Citation Token: {:?}",
-                    self.tokenizer.current_token
-                ),
+            return Ok(MediaLink::Reference {
+                xref: xref.clone(),
+                crop,
+                title,
+            });
+        }
+
+        let mut files = Vec::new();
+        let mut title = None;
+        for child in &node.children {
+            match child.tag.as_str() {
+                "FILE" => files.push(self.parse_media_file(child)?),
+                "TITL" => title = Some(self.take_line_value(child)?),
+                _ => self.skip_unhandled_tag("MediaLink", child)?,
             }
         }
-        citation
+
+        Ok(MediaLink::Inline { files, title })
     }
 
-    fn take_continued_text(&mut self, level: u8) -> String {
-        let mut value = self.take_line_value();
+    fn parse_crop(&mut self, node: &GedcomNode) -> Result<Crop, GedcomError> {
+        let mut crop = Crop::default();
 
-        loop {
-            if let Token::Level(cur_level) = self.tokenizer.current_token {
-                if cur_level <= level {
-                    break;
+        for child in &node.children {
+            let side = match child.tag.as_str() {
+                "TOP" => &mut crop.top,
+                "LEFT" => &mut crop.left,
+                "HEIGHT" => &mut crop.height,
+                "WIDTH" => &mut crop.width,
+                _ => {
+                    self.skip_unhandled_tag("Crop", child)?;
+                    continue;
                 }
+            };
+            if let Ok(pixels) = self.take_line_value(child)?.parse() {
+                *side = Some(pixels);
+            } else {
+                let error = Parser::invalid_value("Crop", child);
+                self.recover(error, Severity::Error, child)?;
             }
-            match &self.tokenizer.current_token {
-                Token::Tag(tag) => match tag.as_str() {
-                    "CONT" => {
-                        value.push('\n');
-                        value.push_str(&self.take_line_value())
-                    }
-                    "CONC" => {
-                        value.push(' ');
-                        value.push_str(&self.take_line_value())
+        }
+
+        Ok(crop)
+    }
+
+    fn parse_media_file(&mut self, node: &GedcomNode) -> Result<MediaFile, GedcomError> {
+        let mut file = MediaFile {
+            value: Some(self.take_line_value(node)?),
+            ..MediaFile::default()
+        };
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "FORM" => {
+                    file.form = Some(self.take_line_value(child)?);
+                    // 5.5.1 files write the source media type as `TYPE`, 5.5 and 5.5.5 as `MEDI`
+                    for form_child in &child.children {
+                        match form_child.tag.as_str() {
+                            "TYPE" | "MEDI" => {
+                                file.media_type = Some(self.take_line_value(form_child)?);
+                            }
+                            _ => self.skip_unhandled_tag("MediaFile", form_child)?,
+                        }
                     }
-                    _ => panic!("{} Unhandled Continuation Tag: {}", self.dbg(), tag),
-                },
-                Token::Level(_) => self.tokenizer.next_token(),
-                _ => panic!(
-                    "Unhandled Continuation Token: {:?}",
-                    self.tokenizer.current_token
-                ),
+                }
+                "TITL" => file.title = Some(self.take_line_value(child)?),
+                "TRAN" => file.translations.push(self.parse_translation(child)?),
+                _ => self.skip_unhandled_tag("MediaFile", child)?,
+            }
+        }
+
+        Ok(file)
+    }
+
+    fn parse_user_reference(&mut self, node: &GedcomNode) -> Result<UserReference, GedcomError> {
+        let mut reference = UserReference {
+            value: Some(self.take_line_value(node)?),
+            reference_type: None,
+        };
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "TYPE" => reference.reference_type = Some(self.take_line_value(child)?),
+                _ => self.skip_unhandled_tag("UserReference", child)?,
             }
         }
 
-        value
+        Ok(reference)
+    }
+
+    fn parse_change_date(&mut self, node: &GedcomNode) -> Result<ChangeDate, GedcomError> {
+        let mut change_date = ChangeDate::default();
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "DATE" => {
+                    let date = self.parse_head_date(child)?;
+                    change_date.date = date.value;
+                    change_date.time = date.time;
+                }
+                "NOTE" | "SNOTE" => change_date.notes.push(self.parse_note_ref(child)?),
+                _ => self.skip_unhandled_tag("ChangeDate", child)?,
+            }
+        }
+
+        Ok(change_date)
+    }
+
+    /// Takes the value of a node along with its `CONT` and `CONC` continuation lines, any other
+    /// substructure is unhandled
+    fn take_continued_text(
+        &mut self,
+        node: &GedcomNode,
+        record: &'static str,
+    ) -> Result<String, GedcomError> {
+        for child in &node.children {
+            self.skip_unhandled_tag(record, child)?;
+        }
+        Ok(node.text().unwrap_or_default())
+    }
+
+    /// Takes the value of a node that requires one along with its continuation lines, a missing
+    /// value leniently reads as empty
+    fn take_line_value(&mut self, node: &GedcomNode) -> Result<String, GedcomError> {
+        if let Some(value) = node.text() {
+            return Ok(value);
+        }
+        let error = GedcomError::MissingValue {
+            line: node.line,
+            tag: node.tag.clone(),
+        };
+        self.recover(error, Severity::Error, node)?;
+        Ok(String::new())
     }
 
-    fn take_line_value(&mut self) -> String {
-        let value: String;
-        self.tokenizer.next_token();
+    /// Takes the value of a `DATE`, which GEDCOM 7.0 leaves empty when its `PHRASE` describes it
+    fn take_date(&mut self, node: &GedcomNode) -> Result<Option<String>, GedcomError> {
+        if node.value.is_none() && node.child("PHRASE").is_some() {
+            return Ok(None);
+        }
+        self.take_line_value(node).map(Some)
+    }
 
-        if let Token::LineValue(val) = &self.tokenizer.current_token {
-            value = val.to_string();
-        } else {
-            panic!(
-                "{} Expected LineValue, found {:?}",
-                self.dbg(),
-                self.tokenizer.current_token
-            );
+    /// Takes the free text GEDCOM 7.0 describes a value with, the `PHRASE` beneath it
+    fn take_phrase(node: &GedcomNode) -> Option<String> {
+        node.child("PHRASE").and_then(GedcomNode::text)
+    }
+
+    /// Takes a pointer to another record, reporting it when malformed
+    fn take_pointer(&mut self, node: &GedcomNode) -> Result<String, GedcomError> {
+        let pointer = self.take_line_value(node)?;
+        if node.value.is_some() {
+            self.check_pointer(&pointer, node);
         }
-        self.tokenizer.next_token();
-        value
+        Ok(pointer)
     }
 
-    fn dbg(&self) -> String {
-        format!("line {}:", self.tokenizer.line)
+    /// Reports a cross-reference identifier or pointer that does not follow the `@` alphanumeric
+    /// `@` syntax of at most 20 characters between the `@` signs. It is used as it is regardless
+    /// of strictness.
+    fn check_pointer(&mut self, pointer: &str, node: &GedcomNode) {
+        let inner = pointer
+            .strip_prefix('@')
+            .and_then(|rest| rest.strip_suffix('@'));
+        let is_valid = inner.is_some_and(|inner| {
+            inner.starts_with(|c: char| c.is_ascii_alphanumeric())
+                && !inner.contains('@')
+                && inner.chars().count() <= 20
+        });
+        if !is_valid {
+            let error = GedcomError::InvalidPointer {
+                line: node.line,
+                value: pointer.to_string(),
+            };
+            self.report(&error, Severity::Warning, node);
+        }
+    }
+
+    /// Handles a tag the structure being parsed does not allow, lenient parsing skips it along
+    /// with its substructures
+    fn skip_unhandled_tag(
+        &mut self,
+        record: &'static str,
+        node: &GedcomNode,
+    ) -> Result<(), GedcomError> {
+        if node.is_continuation() {
+            // read along with the value they continue
+            return Ok(());
+        }
+        let error = Parser::unexpected_tag(record, node);
+        self.recover(error, Severity::Warning, node)
+    }
+
+    /// Fails with `error` when parsing strictly, otherwise records it as a diagnostic
+    fn recover(
+        &mut self,
+        error: GedcomError,
+        severity: Severity,
+        node: &GedcomNode,
+    ) -> Result<(), GedcomError> {
+        if self.options.strictness == Strictness::Strict {
+            return Err(error);
+        }
+        self.report(&error, severity, node);
+        Ok(())
+    }
+
+    /// Fails with `error` when parsing strictly, otherwise records it as a diagnostic without a
+    /// tag, for a line whose level or tag could not be read
+    fn recover_line(&mut self, error: GedcomError, level: u8) -> Result<(), GedcomError> {
+        if self.options.strictness == Strictness::Strict {
+            return Err(error);
+        }
+        self.diagnostics.push(Diagnostic {
+            line: error.line().unwrap_or(self.tokenizer.line),
+            level,
+            tag: String::new(),
+            severity: Severity::Error,
+            message: error.message(),
+        });
+        Ok(())
+    }
+
+    fn report(&mut self, error: &GedcomError, severity: Severity, node: &GedcomNode) {
+        self.diagnostics.push(Diagnostic {
+            line: error.line().unwrap_or(node.line),
+            level: node.level,
+            tag: node.tag.clone(),
+            severity,
+            message: error.message(),
+        });
+    }
+
+    fn unexpected_tag(record: &'static str, node: &GedcomNode) -> GedcomError {
+        GedcomError::UnexpectedTag {
+            line: node.line,
+            record,
+            tag: node.tag.clone(),
+        }
+    }
+
+    fn invalid_value(record: &'static str, node: &GedcomNode) -> GedcomError {
+        GedcomError::InvalidValue {
+            line: node.line,
+            record,
+            tag: node.tag.clone(),
+            value: node.value.clone().unwrap_or_default(),
+        }
+    }
+
+    fn unexpected_token(&self, record: &'static str) -> GedcomError {
+        GedcomError::UnexpectedToken {
+            line: self.tokenizer.line,
+            record,
+            token: format!("{:?}", self.tokenizer.current_token()),
+        }
+    }
+}
+
+/// Iterator over the typed records of a file, see `Parser::records`
+pub struct Records<'p, 'a> {
+    parser: &'p mut Parser<'a>,
+    done: bool,
+}
+
+impl Iterator for Records<'_, '_> {
+    type Item = Result<Record, GedcomError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let record = self.parser.next_record().transpose();
+        self.done = matches!(record, None | Some(Err(_) | Ok(Record::Trailer)));
+        record
+    }
+}
+
+/// Parses the contents on `options.threads` threads, each parsing a part that starts at a
+/// level-0 line, and merges the records in their original order
+fn parse_parallel(
+    contents: &str,
+    offset: usize,
+    options: ParserOptions,
+) -> (Result<GedcomData, GedcomError>, Vec<Diagnostic>) {
+    let parts = split_records(contents, options.threads);
+    // the parts after the header are read with the version it declares
+    let mut header = Parser::new(contents);
+    if let Ok(Some(node)) = header.next_node() {
+        header.set_version(header_version(&node));
+    }
+    let version = header.version;
+    let results: Vec<_> = std::thread::scope(|scope| {
+        let mut line = 0;
+        let mut handles = Vec::with_capacity(parts.len());
+        for &(start, end) in &parts {
+            let part = &contents[start..end];
+            handles.push(scope.spawn(move || {
+                let tokenizer = Tokenizer::starting_at(part, line, offset + start);
+                parse_part(tokenizer, options, version)
+            }));
+            let lines = tokenizer::count_terminators(part.as_bytes());
+            line += u32::try_from(lines).unwrap_or(u32::MAX);
+        }
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    });
+
+    let mut data = GedcomData::default();
+    let mut all_diagnostics = Vec::new();
+    for (records, diagnostics) in results {
+        all_diagnostics.extend(diagnostics);
+        let records = match records {
+            Ok(records) => records,
+            Err(error) => return (Err(error), all_diagnostics),
+        };
+        for record in records {
+            if let Record::Trailer = record {
+                return (Ok(data), all_diagnostics);
+            }
+            data.add_record(record);
+        }
+    }
+    (Ok(data), all_diagnostics)
+}
+
+/// Parses the records of a part of the contents along with the problems skipped over
+fn parse_part(
+    tokenizer: Tokenizer<'_>,
+    options: ParserOptions,
+    version: GedcomVersion,
+) -> (Result<Vec<Record>, GedcomError>, Vec<Diagnostic>) {
+    let mut parser = Parser::new_with_tokenizer(tokenizer);
+    parser.set_options(options);
+    parser.set_version(version);
+    let records = parser.records().collect();
+    (records, parser.diagnostics)
+}
+
+/// The version declared by the `GEDC.VERS` of a header node
+fn header_version(node: &GedcomNode) -> GedcomVersion {
+    (node.child("GEDC"))
+        .and_then(|gedcom| gedcom.child("VERS"))
+        .and_then(|version| version.value.as_deref())
+        .map_or_else(GedcomVersion::default, GedcomVersion::from_number)
+}
+
+/// Splits the contents into at most `parts` byte ranges of similar length, each starting at
+/// a level-0 line
+fn split_records(contents: &str, parts: usize) -> Vec<(usize, usize)> {
+    let bytes = contents.as_bytes();
+    let mut starts = vec![0];
+    for part in 1..parts {
+        let target = (bytes.len() / parts * part).max(starts[starts.len() - 1]);
+        match bytes[target..]
+            .windows(3)
+            .position(|window| matches!(window, [b'\r' | b'\n', b'0', b' ']))
+        {
+            Some(index) => starts.push(target + index + 1),
+            None => break,
+        }
     }
+    let ends = starts.iter().skip(1).copied().chain([bytes.len()]);
+    starts.iter().copied().zip(ends).collect()
 }
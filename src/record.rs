@@ -0,0 +1,32 @@
+//! Typed records yielded one at a time while parsing
+use crate::types::{
+    Family, Header, Individual, Media, Note, Repository, Source, Submission, Submitter,
+};
+use crate::GedcomNode;
+
+/// A typed level-0 record, as yielded by `Parser::records`
+#[derive(Debug)]
+pub enum Record {
+    /// The `HEAD` record with metadata about the file
+    Header(Box<Header>),
+    /// A `SUBM` record
+    Submitter(Submitter),
+    /// A `SUBN` record, the submission of the file to the LDS church
+    Submission(Submission),
+    /// An `INDI` record
+    Individual(Individual),
+    /// A `FAM` record
+    Family(Family),
+    /// A `SOUR` record
+    Source(Source),
+    /// A `REPO` record
+    Repository(Repository),
+    /// A shared `NOTE` record
+    Note(Note),
+    /// An `OBJE` record
+    Media(Media),
+    /// A record with a user-defined tag, _ie._ `_PLAC`
+    Extension(GedcomNode),
+    /// The `TRLR` that ends the file
+    Trailer,
+}
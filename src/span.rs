@@ -0,0 +1,32 @@
+//! Locations of tokens, lines and records within a file
+
+/// A location within the contents of a file
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Position {
+    /// Line number, starting at 1
+    pub line: u32,
+    /// Character of the line, starting at 1
+    pub column: u32,
+    /// Byte offset within the decoded contents
+    pub offset: usize,
+}
+
+/// The text between two positions of a file, the end being exclusive
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Span {
+    /// Position of the first character
+    pub start: Position,
+    /// Position just past the last character
+    pub end: Position,
+}
+
+impl Span {
+    /// The span from the start of this one to the end of `other`
+    #[must_use]
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            start: self.start,
+            end: other.end,
+        }
+    }
+}
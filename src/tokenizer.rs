@@ -1,137 +1,472 @@
 //! Handles the tokenization of a GEDCOM file
-use std::str::Chars;
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::io::BufRead;
+
+use crate::encoding::{FileEncoding, LineDecoder};
+use crate::error::GedcomError;
+use crate::span::{Position, Span};
+use crate::types::is_pointer;
 
 /// The base enum of Token types
 ///
 /// making use of [GEDCOM Standard Release 5.5.1](https://edge.fscdn.org/assets/img/documents/ged551-5bac5e57fe88dd37df0e153d9c515335.pdf), p.11
 /// `gedcom_line: level + delim + [optional_xref_ID] + tag + [optional_line_value] + terminator`
+///
+/// Tags, values and pointers borrow from the contents being tokenized. They own their text
+/// only when the contents are owned, having been decoded or read from a reader, and for values
+/// with `@@` unescaped.
 #[derive(Debug, PartialEq)]
-pub enum Token {
+pub enum Token<'a> {
     /// The `level`, denoting the depth within the tree
     Level(u8),
     /// The `tag`, a four character code that distinguishes datatypes
-    Tag(String),
-    /// The value of the data: `optional_line_value`
-    LineValue(String),
-    /// The `optional_xref_ID` used throughout the file to refer to a particular face
-    Pointer(String),
+    Tag(Cow<'a, str>),
+    /// The value of the data: `optional_line_value`, with `@@` unescaped to `@`
+    LineValue(Cow<'a, str>),
+    /// An escape sequence starting the line value, _ie._ the calendar escape `@#DJULIAN@`
+    Escape(Cow<'a, str>),
+    /// The `optional_xref_ID` used throughout the file to refer to a particular face, or a line
+    /// value pointing to one
+    Pointer(Cow<'a, str>),
     /// End-of-file indicator
     EOF,
     /// The initial token value, indicating nothing
     None,
 }
 
-/// The tokenizer that turns the gedcom characters into a list of tokens
+impl Token<'_> {
+    /// The token owning its text, no longer borrowing from the contents
+    #[must_use]
+    pub fn into_owned(self) -> Token<'static> {
+        let own = |text: Cow<'_, str>| Cow::Owned(text.into_owned());
+        match self {
+            Token::Level(level) => Token::Level(level),
+            Token::Tag(text) => Token::Tag(own(text)),
+            Token::LineValue(text) => Token::LineValue(own(text)),
+            Token::Escape(text) => Token::Escape(own(text)),
+            Token::Pointer(text) => Token::Pointer(own(text)),
+            Token::EOF => Token::EOF,
+            Token::None => Token::None,
+        }
+    }
+}
+
+/// The kind of the current token, its text is held as a byte range of the contents
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TokenKind {
+    Level(u8),
+    Tag,
+    LineValue,
+    Escape,
+    Pointer,
+    Eof,
+    None,
+}
+
+/// The tokenizer that turns the gedcom contents into a list of tokens
+///
+/// Works on the bytes of the contents. Every delimiter of the grammar is ASCII, so tokens are
+/// sliced from the contents without copying. Contents read from a reader are held one line at a
+/// time.
 pub struct Tokenizer<'a> {
-    /// The active token type
-    pub current_token: Token,
-    /// Current character tokenizer is parsing
-    current_char: char,
-    /// An iterator of charaters of the Gedcom file contents
-    chars: Chars<'a>,
+    /// The Gedcom file contents, owned when they had to be decoded or read
+    input: Cow<'a, str>,
+    /// Source of the following lines when reading from a reader
+    reader: Option<LineDecoder<'a>>,
+    /// Byte offset of the next byte to tokenize
+    position: usize,
+    /// Kind of the active token
+    kind: TokenKind,
+    /// Byte range of the active token's text
+    start: usize,
+    end: usize,
+    /// The active line value with `@@` unescaped, when it contains any
+    unescaped: Option<String>,
+    /// Whether only an `@@` starting a line value is unescaped, as in GEDCOM 7.0
+    leading_escape_only: bool,
+    /// Byte offset of the start of the current line
+    line_start: usize,
+    /// Length of the contents read before the current line, when reading from a reader
+    read_offset: usize,
+    /// Text read from a reader since the last `take_raw`, when the raw text is being kept
+    raw: Option<String>,
+    /// Byte offset of the contents not yet taken by `take_raw`
+    raw_start: usize,
     /// The current line number of the file we are parsing
     pub line: u32,
+    /// The level of the line we are parsing
+    pub level: u8,
 }
 
 impl<'a> Tokenizer<'a> {
-    /// Creates a new tokenizer for a char interator of gedcom file contents
+    /// Creates a new tokenizer for the gedcom file contents, skipping a leading byte order mark
     #[must_use]
-    pub fn new(chars: Chars<'a>) -> Tokenizer {
+    pub fn new(input: impl Into<Cow<'a, str>>) -> Tokenizer<'a> {
+        let input = input.into();
+        let position = if input.starts_with('\u{feff}') {
+            '\u{feff}'.len_utf8()
+        } else {
+            0
+        };
         Tokenizer {
-            current_char: '\n',
-            current_token: Token::None,
-            chars,
-            line: 0,
+            input,
+            reader: None,
+            position,
+            kind: TokenKind::None,
+            start: 0,
+            end: 0,
+            unescaped: None,
+            leading_escape_only: false,
+            // a byte order mark is not part of the first line
+            line_start: position,
+            read_offset: 0,
+            raw: None,
+            raw_start: 0,
+            line: 1,
+            level: 0,
         }
     }
 
+    /// Creates a tokenizer for a part of the gedcom file contents that starts after `line` lines
+    /// and `offset` bytes of the file
+    pub(crate) fn starting_at(input: &'a str, line: u32, offset: usize) -> Tokenizer<'a> {
+        let mut tokenizer = Tokenizer::new(input);
+        tokenizer.line = line + 1;
+        tokenizer.read_offset = offset;
+        tokenizer
+    }
+
+    /// Creates a new tokenizer reading the gedcom file contents one line at a time, decoding
+    /// them like `encoding::decode` does
+    #[must_use]
+    pub fn from_reader(reader: impl BufRead + 'a) -> Tokenizer<'a> {
+        let mut tokenizer = Tokenizer::new(String::new());
+        tokenizer.reader = Some(LineDecoder::new(reader));
+        tokenizer
+    }
+
+    /// The active token, borrowing from the contents rather than the tokenizer when they are
+    /// borrowed
+    #[must_use]
+    pub fn current_token(&self) -> Token<'a> {
+        match self.input {
+            Cow::Borrowed(input) if self.unescaped.is_none() => {
+                token(self.kind, &input[self.start..self.end])
+            }
+            _ => self.token().into_owned(),
+        }
+    }
+
+    /// The active token, borrowing from the tokenizer
+    fn token(&self) -> Token<'_> {
+        let text = &self.input[self.start..self.end];
+        token(self.kind, self.unescaped.as_deref().unwrap_or(text))
+    }
+
+    /// The location of the active token.
+    /// Level tokens span the level number, the end of file an empty span after the last line.
+    #[must_use]
+    pub fn current_span(&self) -> Span {
+        Span {
+            start: self.position_of(self.start),
+            end: self.position_of(self.end),
+        }
+    }
+
+    fn position_of(&self, index: usize) -> Position {
+        let characters = self.input[self.line_start..index].chars().count();
+        Position {
+            line: self.line,
+            column: u32::try_from(characters + 1).unwrap_or(u32::MAX),
+            offset: self.read_offset + index,
+        }
+    }
+
+    /// The contents along with their byte offset when nothing has been tokenized yet and they
+    /// are held in full rather than read from a reader
+    pub(crate) fn untokenized(&self) -> Option<(&str, usize)> {
+        if self.reader.is_some() || self.kind != TokenKind::None {
+            return None;
+        }
+        Some((&self.input[self.position..], self.position))
+    }
+
+    /// The encoding of the contents read from a reader, as detected or declared so far
+    pub(crate) fn reader_encoding(&self) -> Option<FileEncoding> {
+        self.reader.as_ref().map(LineDecoder::encoding)
+    }
+
+    /// Skips the rest of the contents
+    pub(crate) fn finish(&mut self) {
+        self.position = self.input.len();
+        (self.start, self.end) = (self.position, self.position);
+        self.kind = TokenKind::Eof;
+    }
+
+    /// Unescapes only an `@@` starting a line value from now on, as GEDCOM 7.0 escapes no other
+    pub(crate) fn unescape_leading_only(&mut self, leading_only: bool) {
+        self.leading_escape_only = leading_only;
+    }
+
+    /// Keeps the text that is tokenized for `take_raw`, which would otherwise be dropped along
+    /// with each line read from a reader
+    pub(crate) fn keep_raw(&mut self) {
+        self.raw.get_or_insert_with(String::new);
+    }
+
+    /// The exact text tokenized since the last call, up to the start of the active token. The
+    /// first call starts from the beginning of the contents, including a byte order mark.
+    pub(crate) fn take_raw(&mut self) -> String {
+        let mut raw = self.raw.as_mut().map(std::mem::take).unwrap_or_default();
+        raw.push_str(&self.input[self.raw_start..self.start]);
+        self.raw_start = self.start;
+        raw
+    }
+
     /// Ends the tokenization
     #[must_use]
     pub fn done(&self) -> bool {
-        self.current_token == Token::EOF
+        self.kind == TokenKind::Eof
     }
 
     /// Loads the next token into state
-    pub fn next_token(&mut self) {
-        if self.current_char == '\0' {
-            self.current_token = Token::EOF;
-            return;
-        }
-
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GedcomError` if a line does not start with a level number from 0 to 99 or the
+    /// tokens of a line are out of order. The line can be skipped with `skip_line`.
+    pub fn next_token(&mut self) -> Result<(), GedcomError> {
+        self.unescaped = None;
         // level number is at the start of each line.
-        if self.current_char == '\r' {
-            self.next_char();
-        }
-        if self.current_char == '\n' {
-            self.next_char();
-            self.current_token = Token::Level(self.extract_number());
-            self.line += 1;
-            return;
+        if self.kind == TokenKind::None || self.current_byte().is_some_and(is_terminator) {
+            if !self.start_line()? {
+                self.kind = TokenKind::Eof;
+                (self.start, self.end) = (self.position, self.position);
+                return Ok(());
+            }
+            self.level = self.extract_number()?;
+            self.kind = TokenKind::Level(self.level);
+            return Ok(());
         }
 
+        let delimiter = self.position;
         self.skip_whitespace();
 
+        let Some(byte) = self.current_byte() else {
+            self.kind = TokenKind::Eof;
+            (self.start, self.end) = (self.position, self.position);
+            return Ok(());
+        };
+
         // handle tag with trailing whitespace
-        if self.current_char == '\n' {
-            // println!("line {}: trailing whitespace {:?}", self.line, self.current_token);
-            self.next_token();
-            return;
+        if is_terminator(byte) {
+            return self.next_token();
         }
 
-        self.current_token = match self.current_token {
-            Token::Level(_) => {
-                if self.current_char == '@' {
-                    Token::Pointer(self.extract_word())
+        self.kind = match self.kind {
+            TokenKind::Level(_) => {
+                self.extract_word();
+                if byte == b'@' {
+                    TokenKind::Pointer
+                } else {
+                    TokenKind::Tag
+                }
+            }
+            TokenKind::Pointer => {
+                self.extract_word();
+                TokenKind::Tag
+            }
+            kind @ (TokenKind::Tag | TokenKind::Escape) => {
+                if kind == TokenKind::Tag && self.extract_escape() {
+                    TokenKind::Escape
                 } else {
-                    Token::Tag(self.extract_word())
+                    // a single delimiter precedes the value, which keeps any further leading
+                    // spaces
+                    self.position = self.position.min(delimiter + 1);
+                    self.extract_value();
+                    let value = &self.input[self.start..self.end];
+                    if kind == TokenKind::Tag && self.unescaped.is_none() && is_pointer(value) {
+                        TokenKind::Pointer
+                    } else {
+                        TokenKind::LineValue
+                    }
                 }
             }
-            Token::Pointer(_) => Token::Tag(self.extract_word()),
-            Token::Tag(_) => Token::LineValue(self.extract_value()),
-            _ => panic!(
-                "line {}: Tokenization error! {:?}",
-                self.line, self.current_token
-            ),
+            _ => {
+                return Err(GedcomError::UnexpectedToken {
+                    line: self.line,
+                    record: "Line",
+                    token: format!("{:?}", self.token()),
+                })
+            }
         };
+        Ok(())
     }
 
-    fn next_char(&mut self) {
-        self.current_char = self.chars.next().unwrap_or('\0');
+    /// Skips the rest of the current line, so that the next token is the level of the next one
+    pub fn skip_line(&mut self) {
+        self.take_while(|byte| !is_terminator(byte));
     }
 
-    fn extract_number(&mut self) -> u8 {
-        let mut digits: Vec<char> = Vec::new();
-        while self.current_char.is_digit(10) {
-            digits.push(self.current_char);
-            self.next_char();
+    /// Moves past line terminators, blank lines and the indentation of the next line, returning
+    /// `false` at the end of the file
+    fn start_line(&mut self) -> Result<bool, GedcomError> {
+        let mut terminated = false;
+        loop {
+            match self.current_byte() {
+                Some(byte) if is_terminator(byte) => {
+                    self.position += terminator_length(&self.input.as_bytes()[self.position..]);
+                    self.line += 1;
+                    self.line_start = self.position;
+                    terminated = true;
+                }
+                Some(byte) if byte.is_ascii_whitespace() => self.position += 1,
+                Some(_) => return Ok(true),
+                None => {
+                    if !self.read_line()? {
+                        if terminated {
+                            // the last terminator does not start another line
+                            self.line -= 1;
+                        }
+                        return Ok(false);
+                    }
+                }
+            }
         }
-
-        digits.iter().collect::<String>().parse::<u8>().unwrap()
     }
 
-    fn extract_word(&mut self) -> String {
-        let mut letters: Vec<char> = Vec::new();
-        while !self.current_char.is_whitespace() && self.current_char != '\0' {
-            letters.push(self.current_char);
-            self.next_char();
+    /// Replaces the contents with the next line of the reader, returning `false` at the end of
+    /// the file
+    fn read_line(&mut self) -> Result<bool, GedcomError> {
+        let Some(reader) = &mut self.reader else {
+            return Ok(false);
+        };
+        if let Some(raw) = &mut self.raw {
+            raw.push_str(&self.input[self.raw_start..]);
         }
+        self.raw_start = 0;
+        let mut line = std::mem::take(&mut self.input).into_owned();
+        self.read_offset += line.len();
+        reader.read_line(&mut line)?;
+        self.input = Cow::Owned(line);
+        self.position = 0;
+        self.start = 0;
+        self.end = 0;
+        self.line_start = 0;
+        Ok(!self.input.is_empty())
+    }
 
-        letters.iter().collect::<String>()
+    fn current_byte(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.position).copied()
     }
 
-    fn extract_value(&mut self) -> String {
-        let mut letters: Vec<char> = Vec::new();
-        while self.current_char != '\n' {
-            letters.push(self.current_char);
-            self.next_char();
+    /// Takes bytes from the current position while `predicate` holds, returning their range
+    fn take_while(&mut self, predicate: impl Fn(u8) -> bool) -> (usize, usize) {
+        let start = self.position;
+        let bytes = self.input.as_bytes();
+        while self.position < bytes.len() && predicate(bytes[self.position]) {
+            self.position += 1;
         }
+        // only ever stops at an ASCII byte or the end, both of which are char boundaries
+        (start, self.position)
+    }
 
-        letters.iter().collect::<String>()
+    fn extract_number(&mut self) -> Result<u8, GedcomError> {
+        (self.start, self.end) = self.take_while(|byte| byte.is_ascii_digit());
+        let number = &self.input[self.start..self.end];
+        match number.parse::<u8>() {
+            Ok(level) if level <= 99 => Ok(level),
+            _ => {
+                let (_, end) = self.take_while(|byte| !byte.is_ascii_whitespace());
+                Err(GedcomError::InvalidLevel {
+                    line: self.line,
+                    value: self.input[self.start..end].to_string(),
+                })
+            }
+        }
+    }
+
+    fn extract_word(&mut self) {
+        (self.start, self.end) = self.take_while(|byte| !byte.is_ascii_whitespace());
+    }
+
+    fn extract_value(&mut self) {
+        (self.start, self.end) = self.take_while(|byte| !is_terminator(byte));
+        let value = &self.input[self.start..self.end];
+        self.unescaped = if self.leading_escape_only {
+            value
+                .strip_prefix('@')
+                .filter(|rest| rest.starts_with('@'))
+                .map(str::to_string)
+        } else {
+            value.contains("@@").then(|| value.replace("@@", "@"))
+        };
+    }
+
+    /// Takes an escape sequence, `@#` followed by text and a closing `@`, if one starts at the
+    /// current position
+    fn extract_escape(&mut self) -> bool {
+        let rest = &self.input.as_bytes()[self.position..];
+        if !rest.starts_with(b"@#") {
+            return false;
+        }
+        let line_end = rest
+            .iter()
+            .position(|&byte| is_terminator(byte))
+            .unwrap_or(rest.len());
+        let Some(length) = rest[2..line_end].iter().position(|&byte| byte == b'@') else {
+            return false;
+        };
+        self.start = self.position;
+        self.position += length + 3;
+        self.end = self.position;
+        true
     }
 
     fn skip_whitespace(&mut self) {
-        while self.current_char.is_whitespace() && self.current_char != '\n' {
-            self.next_char();
+        self.take_while(|byte| byte.is_ascii_whitespace() && !is_terminator(byte));
+    }
+}
+
+/// The token of the given kind with the given text
+fn token(kind: TokenKind, text: &str) -> Token<'_> {
+    match kind {
+        TokenKind::Level(level) => Token::Level(level),
+        TokenKind::Tag => Token::Tag(Cow::Borrowed(text)),
+        TokenKind::LineValue => Token::LineValue(Cow::Borrowed(text)),
+        TokenKind::Escape => Token::Escape(Cow::Borrowed(text)),
+        TokenKind::Pointer => Token::Pointer(Cow::Borrowed(text)),
+        TokenKind::Eof => Token::EOF,
+        TokenKind::None => Token::None,
+    }
+}
+
+fn is_terminator(byte: u8) -> bool {
+    byte == b'\r' || byte == b'\n'
+}
+
+/// The length of the line terminator at the start of `bytes`: `\r`, `\n`, `\r\n` or `\n\r`
+pub(crate) fn terminator_length(bytes: &[u8]) -> usize {
+    match bytes {
+        [b'\r', b'\n', ..] | [b'\n', b'\r', ..] => 2,
+        [b'\r' | b'\n', ..] => 1,
+        _ => 0,
+    }
+}
+
+/// The number of line terminators in `bytes`
+pub(crate) fn count_terminators(bytes: &[u8]) -> usize {
+    let mut count = 0;
+    let mut position = 0;
+    while position < bytes.len() {
+        match terminator_length(&bytes[position..]) {
+            0 => position += 1,
+            length => {
+                position += length;
+                count += 1;
+            }
         }
     }
+    count
 }
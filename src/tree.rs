@@ -1,9 +1,15 @@
-use crate::types::{Family, Individual, Media, Repository, Source, Submitter};
+use crate::types::{
+    Family, Header, Individual, Media, Note, Repository, Source, Submission, Submitter,
+};
+use crate::{GedcomNode, Record};
 
 #[derive(Debug, Default)]
 /// The data structure representing all the data within a gedcom file
 pub struct GedcomData {
-    // TODO: header
+    /// Header containing metadata about the file
+    pub header: Option<Header>,
+    /// Submission of the file to the LDS church, pointed to by the header
+    pub submission: Option<Submission>,
     /// List of submitters of the facts
     pub submitters: Vec<Submitter>,
     /// Individuals within the family tree
@@ -16,6 +22,10 @@ pub struct GedcomData {
     pub sources: Vec<Source>,
     /// A multimedia asset linked to a fact
     pub multimedia: Vec<Media>,
+    /// Notes shared between the records that point to them
+    pub notes: Vec<Note>,
+    /// Top-level records with user-defined tags
+    pub extensions: Vec<GedcomNode>,
 }
 
 // should maybe store these by xref if available?
@@ -45,6 +55,43 @@ impl GedcomData {
         self.submitters.push(submitter);
     }
 
+    /// Adds a multimedia record to the tree
+    pub fn add_media(&mut self, media: Media) {
+        self.multimedia.push(media);
+    }
+
+    /// Adds a `Note` to the tree
+    pub fn add_note(&mut self, note: Note) {
+        self.notes.push(note);
+    }
+
+    /// Adds a typed record to the tree, replacing the header or submission if it is one.
+    /// The trailer holds no data and is ignored.
+    pub fn add_record(&mut self, record: Record) {
+        match record {
+            Record::Header(header) => self.header = Some(*header),
+            Record::Submitter(submitter) => self.add_submitter(submitter),
+            Record::Submission(submission) => self.submission = Some(submission),
+            Record::Individual(individual) => self.add_individual(individual),
+            Record::Family(family) => self.add_family(family),
+            Record::Source(source) => self.add_source(source),
+            Record::Repository(repo) => self.add_repository(repo),
+            Record::Note(note) => self.add_note(note),
+            Record::Media(media) => self.add_media(media),
+            Record::Extension(node) => self.extensions.push(node),
+            Record::Trailer => (),
+        }
+    }
+
+    /// The `Submission` the header points to with its `SUBN` tag, if both are present
+    #[must_use]
+    pub fn header_submission(&self) -> Option<&Submission> {
+        let xref = self.header.as_ref()?.submission_tag.as_ref()?;
+        self.submission
+            .as_ref()
+            .filter(|submission| submission.xref.as_ref() == Some(xref))
+    }
+
     /// Outputs a summary of data contained in the tree to stdout
     pub fn stats(&self) {
         println!("----------------------");
@@ -55,6 +102,7 @@ impl GedcomData {
         println!("  families: {}", self.families.len());
         println!("  sources: {}", self.sources.len());
         println!("  multimedia: {}", self.multimedia.len());
+        println!("  notes: {}", self.notes.len());
         println!("----------------------");
     }
 }
@@ -1,34 +1,153 @@
-use crate::types::SourceCitation;
-use std::{fmt, string::ToString};
+use crate::types::{MediaLink, NoteRef, SourceCitation};
+use crate::{GedcomNode, Span};
+use std::fmt;
+
+type Xref = String;
 
 #[allow(clippy::module_name_repetitions)]
 #[derive(Clone, Debug, PartialEq)]
 pub enum EventType {
     Adoption,
+    AdultChristening,
+    Annulment,
+    Baptism,
+    BarMitzvah,
+    BasMitzvah,
     Birth,
+    Blessing,
     Burial,
+    Census,
+    Christening,
+    Confirmation,
+    Cremation,
     Death,
+    Divorce,
+    DivorceFiled,
+    Emigration,
+    Engagement,
+    FirstCommunion,
+    Graduation,
+    Immigration,
     Marriage,
+    MarriageBann,
+    MarriageContract,
+    MarriageLicense,
+    MarriageSettlement,
+    Naturalization,
+    Ordination,
+    Probate,
     Residence,
+    Retirement,
+    Will,
     SourceData(String),
 
+    // generic event (`EVEN`), described by the `TYPE` of the event
+    Event,
+
     // "Other" is used to construct an event without requiring an explicit event type
     Other,
 }
 
-impl ToString for EventType {
-    fn to_string(&self) -> String {
-        format!("{:?}", self)
+impl EventType {
+    /// The tag of the event, the reverse of `Event::from_tag`. Events of source data and
+    /// events without a type of their own are written as a generic `EVEN`.
+    #[must_use]
+    pub fn tag(&self) -> &'static str {
+        match self {
+            EventType::Adoption => "ADOP",
+            EventType::AdultChristening => "CHRA",
+            EventType::Annulment => "ANUL",
+            EventType::Baptism => "BAPM",
+            EventType::BarMitzvah => "BARM",
+            EventType::BasMitzvah => "BASM",
+            EventType::Birth => "BIRT",
+            EventType::Blessing => "BLES",
+            EventType::Burial => "BURI",
+            EventType::Census => "CENS",
+            EventType::Christening => "CHR",
+            EventType::Confirmation => "CONF",
+            EventType::Cremation => "CREM",
+            EventType::Death => "DEAT",
+            EventType::Divorce => "DIV",
+            EventType::DivorceFiled => "DIVF",
+            EventType::Emigration => "EMIG",
+            EventType::Engagement => "ENGA",
+            EventType::FirstCommunion => "FCOM",
+            EventType::Graduation => "GRAD",
+            EventType::Immigration => "IMMI",
+            EventType::Marriage => "MARR",
+            EventType::MarriageBann => "MARB",
+            EventType::MarriageContract => "MARC",
+            EventType::MarriageLicense => "MARL",
+            EventType::MarriageSettlement => "MARS",
+            EventType::Naturalization => "NATU",
+            EventType::Ordination => "ORDN",
+            EventType::Probate => "PROB",
+            EventType::Residence => "RESI",
+            EventType::Retirement => "RETI",
+            EventType::Will => "WILL",
+            EventType::SourceData(_) | EventType::Event | EventType::Other => "EVEN",
+        }
     }
 }
 
+impl fmt::Display for EventType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// How an adopted child is linked to the family that adopted them, the `ADOP` tag under `FAMC`
+#[derive(Clone, Debug, PartialEq)]
+pub enum AdoptedBy {
+    Husband,
+    Wife,
+    Both,
+}
+
+/// Event asserted not to have happened, the `NO` tag of GEDCOM 7.0
+#[derive(Clone, Debug)]
+pub struct NonEvent {
+    pub event: EventType,
+    /// Period in which the event did not happen
+    pub date: Option<String>,
+    /// Free text describing the period, the `PHRASE` tag
+    pub date_phrase: Option<String>,
+    pub citations: Vec<SourceCitation>,
+    pub notes: Vec<NoteRef>,
+}
+
 /// Event fact
 #[derive(Clone)]
 pub struct Event {
     pub event: EventType,
+    /// Line value of the event tag, _ie._ `Y` when an event is known to have happened or the
+    /// descriptor of a generic `EVEN`
+    pub value: Option<String>,
+    /// Descriptive classification of the event, the `TYPE` tag
+    pub event_type: Option<String>,
     pub date: Option<String>,
+    /// Free text describing the date, the `PHRASE` tag of GEDCOM 7.0
+    pub date_phrase: Option<String>,
+    /// Date to sort the event by when its date is not known, the `SDATE` tag of GEDCOM 7.0
+    pub sort_date: Option<String>,
     pub place: Option<String>,
     pub citations: Vec<SourceCitation>,
+    pub notes: Vec<NoteRef>,
+    pub multimedia: Vec<MediaLink>,
+    /// Family the individual is a child of, the `FAMC` tag of birth, christening and adoption
+    /// events
+    pub family: Option<Xref>,
+    /// Parents who adopted the individual, for adoption events
+    pub adopted_by: Option<AdoptedBy>,
+    /// Age of the husband at the time of a family event, the `AGE` tag under `HUSB`
+    pub husband_age: Option<String>,
+    /// Age of the wife at the time of a family event, the `AGE` tag under `WIFE`
+    pub wife_age: Option<String>,
+    pub extensions: Vec<GedcomNode>,
+    /// Location of the lines the event was parsed from
+    pub span: Option<Span>,
+    pub(crate) node: Option<GedcomNode>,
 }
 
 impl Event {
@@ -36,34 +155,93 @@ impl Event {
     pub fn new(etype: EventType) -> Event {
         Event {
             event: etype,
+            value: None,
+            event_type: None,
             date: None,
+            date_phrase: None,
+            sort_date: None,
             place: None,
             citations: Vec::new(),
+            notes: Vec::new(),
+            multimedia: Vec::new(),
+            family: None,
+            adopted_by: None,
+            husband_age: None,
+            wife_age: None,
+            extensions: Vec::new(),
+            span: None,
+            node: None,
         }
     }
 
+    /// The raw line and substructures the event was parsed from
+    #[must_use]
+    pub fn node(&self) -> Option<&GedcomNode> {
+        self.node.as_ref()
+    }
+
     /** converts an event to be of type `SourceData` with `value` as the data */
     pub fn with_source_data(&mut self, value: String) {
         self.event = EventType::SourceData(value);
     }
 
+    /// Creates an `Event` from its tag, returning `None` if the tag is not a known event
     #[must_use]
-    pub fn from_tag(tag: &str) -> Event {
+    pub fn from_tag(tag: &str) -> Option<Event> {
         let etype = match tag {
             "ADOP" => EventType::Adoption,
+            "ANUL" => EventType::Annulment,
+            "BAPM" => EventType::Baptism,
+            "BARM" => EventType::BarMitzvah,
+            "BASM" => EventType::BasMitzvah,
             "BIRT" => EventType::Birth,
+            "BLES" => EventType::Blessing,
             "BURI" => EventType::Burial,
+            "CENS" => EventType::Census,
+            "CHR" => EventType::Christening,
+            "CHRA" => EventType::AdultChristening,
+            "CONF" => EventType::Confirmation,
+            "CREM" => EventType::Cremation,
             "DEAT" => EventType::Death,
+            "DIV" => EventType::Divorce,
+            "DIVF" => EventType::DivorceFiled,
+            "EMIG" => EventType::Emigration,
+            "ENGA" => EventType::Engagement,
+            "EVEN" => EventType::Event,
+            "FCOM" => EventType::FirstCommunion,
+            "GRAD" => EventType::Graduation,
+            "IMMI" => EventType::Immigration,
+            "MARB" => EventType::MarriageBann,
+            "MARC" => EventType::MarriageContract,
+            "MARL" => EventType::MarriageLicense,
             "MARR" => EventType::Marriage,
+            "MARS" => EventType::MarriageSettlement,
+            "NATU" => EventType::Naturalization,
+            "ORDN" => EventType::Ordination,
+            "PROB" => EventType::Probate,
             "RESI" => EventType::Residence,
+            "RETI" => EventType::Retirement,
+            "WILL" => EventType::Will,
             "OTHER" => EventType::Other,
-            _ => panic!("Unrecognized event tag: {}", tag),
+            _ => return None,
+        };
+        Some(Event::new(etype))
+    }
+
+    /// Sets who adopted the individual of an adoption event.
+    /// Returns `false` without changing the event if the text is not `HUSB`, `WIFE` or `BOTH`.
+    pub fn set_adopted_by(&mut self, adopted_by_text: &str) -> bool {
+        self.adopted_by = match adopted_by_text {
+            "HUSB" => Some(AdoptedBy::Husband),
+            "WIFE" => Some(AdoptedBy::Wife),
+            "BOTH" => Some(AdoptedBy::Both),
+            _ => return false,
         };
-        Event::new(etype)
+        true
     }
 
     pub fn add_citation(&mut self, citation: SourceCitation) {
-        self.citations.push(citation)
+        self.citations.push(citation);
     }
 
     #[must_use]
@@ -77,8 +255,22 @@ impl std::fmt::Debug for Event {
         let event_type = format!("{:?} Event", &self.event);
         let mut debug = f.debug_struct(&event_type);
 
+        fmt_optional_value!(debug, "value", &self.value);
+        fmt_optional_value!(debug, "event_type", &self.event_type);
         fmt_optional_value!(debug, "date", &self.date);
+        fmt_optional_value!(debug, "date_phrase", &self.date_phrase);
+        fmt_optional_value!(debug, "sort_date", &self.sort_date);
         fmt_optional_value!(debug, "place", &self.place);
+        debug.field("citations", &self.citations);
+        debug.field("notes", &self.notes);
+        debug.field("multimedia", &self.multimedia);
+        fmt_optional_value!(debug, "family", &self.family);
+        fmt_optional_value!(debug, "adopted_by", &self.adopted_by);
+        fmt_optional_value!(debug, "husband_age", &self.husband_age);
+        fmt_optional_value!(debug, "wife_age", &self.wife_age);
+        debug.field("extensions", &self.extensions);
+        fmt_optional_value!(debug, "span", &self.span);
+        fmt_optional_value!(debug, "node", &self.node);
 
         debug.finish()
     }
@@ -1,4 +1,5 @@
-use crate::types::Event;
+use crate::types::{Event, ExternalId, LdsOrdinance, MediaLink, NonEvent, NoteRef};
+use crate::GedcomNode;
 
 type Xref = String;
 
@@ -14,8 +15,22 @@ pub struct Family {
     pub children: Vec<Xref>,
     pub num_children: Option<u8>,
     events: Vec<Event>,
+    /// Events asserted not to have happened (`NO`)
+    pub non_events: Vec<NonEvent>,
+    /// Sealings of the spouses in the Church of Jesus Christ of Latter-day Saints (`SLGS`)
+    pub lds_ordinances: Vec<LdsOrdinance>,
+    pub notes: Vec<NoteRef>,
+    pub multimedia: Vec<MediaLink>,
+    /// Identifiers of the record in other systems (`EXID`)
+    pub external_ids: Vec<ExternalId>,
+    /// Globally unique identifiers of the record (`UID`)
+    pub unique_ids: Vec<String>,
+    pub extensions: Vec<GedcomNode>,
+    pub(crate) node: Option<GedcomNode>,
 }
 
+parsed_record!(Family);
+
 impl Family {
     #[must_use]
     pub fn new(xref: Option<Xref>) -> Family {
@@ -26,34 +41,47 @@ impl Family {
             children: Vec::new(),
             num_children: None,
             events: Vec::new(),
+            non_events: Vec::new(),
+            lds_ordinances: Vec::new(),
+            notes: Vec::new(),
+            multimedia: Vec::new(),
+            external_ids: Vec::new(),
+            unique_ids: Vec::new(),
+            extensions: Vec::new(),
+            node: None,
         }
     }
 
-    pub fn set_individual1(&mut self, xref: Xref) {
-        match self.individual1 {
-            Some(_) => panic!("First individual of family already exists."),
-            None => self.individual1 = Some(xref),
-        };
+    /// Sets the first individual (HUSB) of the family.
+    /// Returns `false` without changing the family if it already has one.
+    pub fn set_individual1(&mut self, xref: Xref) -> bool {
+        if self.individual1.is_some() {
+            return false;
+        }
+        self.individual1 = Some(xref);
+        true
     }
 
-    pub fn set_individual2(&mut self, xref: Xref) {
-        match self.individual2 {
-            Some(_) => panic!("Second individual of family already exists."),
-            None => self.individual2 = Some(xref),
-        };
+    /// Sets the second individual (WIFE) of the family.
+    /// Returns `false` without changing the family if it already has one.
+    pub fn set_individual2(&mut self, xref: Xref) -> bool {
+        if self.individual2.is_some() {
+            return false;
+        }
+        self.individual2 = Some(xref);
+        true
     }
 
     pub fn add_child(&mut self, xref: Xref) {
         self.children.push(xref);
     }
 
+    pub fn add_note(&mut self, note: NoteRef) {
+        self.notes.push(note);
+    }
+
+    /// Adds an event to the family, which may already have events of the same type
     pub fn add_event(&mut self, event: Event) {
-        let event_type = &event.event;
-        for e in &self.events {
-            if &e.event == event_type {
-                panic!("Family already has a {:?} event", e.event);
-            }
-        }
         self.events.push(event);
     }
 
@@ -61,4 +89,15 @@ impl Family {
     pub fn get_events(&self) -> Vec<Event> {
         self.events.clone()
     }
+
+    /// The events of the family in the order they were added
+    #[must_use]
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// The events of the family, to change them in place
+    pub fn events_mut(&mut self) -> &mut [Event] {
+        &mut self.events
+    }
 }
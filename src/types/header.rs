@@ -0,0 +1,154 @@
+use crate::types::Address;
+use crate::GedcomNode;
+
+type Xref = String;
+
+/// Header (`HEAD`) containing metadata about the transmission of the file
+#[derive(Debug, Default)]
+pub struct Header {
+    /// Version and form of the GEDCOM specification used (`GEDC`)
+    pub gedcom: Option<GedcomMeta>,
+    /// Character set the file is encoded with (`CHAR`)
+    pub encoding: Option<Encoding>,
+    /// The system that produced the file (`SOUR`)
+    pub source: Option<HeadSource>,
+    /// The system the file is intended for (`DEST`)
+    pub destination: Option<String>,
+    /// When the file was created (`DATE`)
+    pub date: Option<HeadDate>,
+    /// Pointer to the `Submitter` of the file (`SUBM`)
+    pub submitter_tag: Option<Xref>,
+    /// Pointer to the `Submission` record of the file (`SUBN`)
+    pub submission_tag: Option<Xref>,
+    /// Name of the file (`FILE`)
+    pub filename: Option<String>,
+    /// Copyright statement for the file (`COPR`)
+    pub copyright: Option<String>,
+    /// Default language of the file's text (`LANG`)
+    pub language: Option<String>,
+    /// Default place hierarchy of the file (`PLAC`)
+    pub place: Option<HeadPlace>,
+    /// Description of the file's contents (`NOTE`)
+    pub note: Option<String>,
+    /// URIs of the extension tags of a GEDCOM 7.0 file (`SCHMA`)
+    pub schema: Vec<SchemaTag>,
+    /// User-defined tags and their substructures
+    pub extensions: Vec<GedcomNode>,
+    pub(crate) node: Option<GedcomNode>,
+}
+
+parsed_record!(Header);
+
+impl Header {
+    /// The version of the specification the file follows, GEDCOM 5 unless `GEDC.VERS` says
+    /// otherwise
+    #[must_use]
+    pub fn version(&self) -> GedcomVersion {
+        (self.gedcom.as_ref())
+            .and_then(|gedcom| gedcom.version.as_deref())
+            .map_or_else(GedcomVersion::default, GedcomVersion::from_number)
+    }
+}
+
+/// Major version of the specification a file follows, which decides the structures it may hold
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GedcomVersion {
+    /// GEDCOM 5.5, 5.5.1 and 5.5.5
+    #[default]
+    V5,
+    /// GEDCOM 7.0 and its minor versions
+    V7,
+}
+
+impl GedcomVersion {
+    /// The major version of a version number, _ie._ `7.0.14`
+    #[must_use]
+    pub fn from_number(number: &str) -> GedcomVersion {
+        if number.trim().split('.').next() == Some("7") {
+            GedcomVersion::V7
+        } else {
+            GedcomVersion::V5
+        }
+    }
+}
+
+/// Extension tag declared in the schema of a GEDCOM 7.0 header, the `TAG` tag of `SCHMA`
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaTag {
+    /// The extension tag, _ie._ `_SKYPEID`
+    pub tag: String,
+    /// URI the tag is defined by
+    pub uri: String,
+}
+
+/// GEDCOM specification used by the file, the `GEDC` tag
+#[derive(Debug, Default)]
+pub struct GedcomMeta {
+    /// Version number of the specification, _ie._ `5.5.1`
+    pub version: Option<String>,
+    /// Form of the file, _ie._ `LINEAGE-LINKED`
+    pub form: Option<String>,
+    /// Version of the form, written by some 5.5.5 files
+    pub form_version: Option<String>,
+}
+
+/// Character set of the file, the `CHAR` tag
+#[derive(Debug, Default)]
+pub struct Encoding {
+    /// Name of the character set, _ie._ `UTF-8` or `ANSEL`
+    pub value: Option<String>,
+    /// Version of the character set
+    pub version: Option<String>,
+}
+
+/// System that produced the file, the `SOUR` tag of the header
+#[derive(Debug, Default)]
+pub struct HeadSource {
+    /// Approved system id of the program
+    pub value: Option<String>,
+    /// Version of the program
+    pub version: Option<String>,
+    /// Name of the program
+    pub name: Option<String>,
+    /// Business that produced the program
+    pub corporation: Option<Corporation>,
+    /// Electronic data source the file was taken from
+    pub data: Option<HeadSourceData>,
+}
+
+/// Business that produced the program writing the file, the `CORP` tag
+#[derive(Debug, Default)]
+pub struct Corporation {
+    pub value: Option<String>,
+    pub address: Option<Address>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub fax: Option<String>,
+    pub website: Option<String>,
+}
+
+/// Electronic data source the file was taken from, the `DATA` tag of the header source
+#[derive(Debug, Default)]
+pub struct HeadSourceData {
+    /// Name of the data source
+    pub value: Option<String>,
+    /// Publication date of the data source
+    pub date: Option<String>,
+    /// Copyright statement of the data source
+    pub copyright: Option<String>,
+}
+
+/// Transmission date of the file, the `DATE` tag of the header
+#[derive(Debug, Default)]
+pub struct HeadDate {
+    pub value: Option<String>,
+    /// Transmission time, the `TIME` tag
+    pub time: Option<String>,
+}
+
+/// Default place hierarchy of the file, the `PLAC` tag of the header
+#[derive(Debug, Default)]
+pub struct HeadPlace {
+    /// Comma-separated jurisdictions of the hierarchy, the `FORM` tag
+    pub form: Option<String>,
+}
@@ -1,4 +1,5 @@
-use crate::types::Event;
+use crate::types::{Event, ExternalId, LdsOrdinance, MediaLink, NonEvent, NoteRef};
+use crate::GedcomNode;
 
 type Xref = String;
 
@@ -9,9 +10,25 @@ pub struct Individual {
     pub name: Option<Name>,
     pub sex: Gender,
     pub events: Vec<Event>,
+    /// Events asserted not to have happened (`NO`)
+    pub non_events: Vec<NonEvent>,
+    /// Ordinances of the Church of Jesus Christ of Latter-day Saints
+    pub lds_ordinances: Vec<LdsOrdinance>,
     pub families: Vec<FamilyLink>,
+    pub notes: Vec<NoteRef>,
+    pub multimedia: Vec<MediaLink>,
+    /// Identifiers of the record in other systems (`EXID`)
+    pub external_ids: Vec<ExternalId>,
+    /// Globally unique identifiers of the record (`UID`)
+    pub unique_ids: Vec<String>,
+    /// Permanent record file number of the record, the `RFN` tag of GEDCOM 5.5.1
+    pub record_file_number: Option<String>,
+    pub extensions: Vec<GedcomNode>,
+    pub(crate) node: Option<GedcomNode>,
 }
 
+parsed_record!(Individual);
+
 impl Individual {
     #[must_use]
     pub fn new(xref: Option<Xref>) -> Individual {
@@ -20,14 +37,23 @@ impl Individual {
             name: None,
             sex: Gender::Unknown,
             events: Vec::new(),
+            non_events: Vec::new(),
+            lds_ordinances: Vec::new(),
             families: Vec::new(),
+            notes: Vec::new(),
+            multimedia: Vec::new(),
+            external_ids: Vec::new(),
+            unique_ids: Vec::new(),
+            record_file_number: None,
+            extensions: Vec::new(),
+            node: None,
         }
     }
 
     pub fn add_family(&mut self, link: FamilyLink) {
         let mut do_add = true;
         let xref = &link.0;
-        for FamilyLink(family, _, _) in &self.families {
+        for FamilyLink(family, _, _, _) in &self.families {
             if family.as_str() == xref.as_str() {
                 do_add = false;
             }
@@ -40,6 +66,10 @@ impl Individual {
     pub fn add_event(&mut self, event: Event) {
         self.events.push(event);
     }
+
+    pub fn add_note(&mut self, note: NoteRef) {
+        self.notes.push(note);
+    }
 }
 
 /// Gender of an `Individual`
@@ -53,13 +83,13 @@ pub enum Gender {
 }
 
 #[derive(Debug)]
-enum FamilyLinkType {
+pub enum FamilyLinkType {
     Spouse,
     Child,
 }
 
 #[derive(Debug)]
-enum Pedigree {
+pub enum Pedigree {
     Adopted,
     Birth,
     Foster,
@@ -67,27 +97,36 @@ enum Pedigree {
 }
 
 #[derive(Debug)]
-pub struct FamilyLink(Xref, FamilyLinkType, Option<Pedigree>);
+pub struct FamilyLink(
+    pub Xref,
+    pub FamilyLinkType,
+    pub Option<Pedigree>,
+    pub Vec<GedcomNode>,
+);
 
 impl FamilyLink {
+    /// Creates a link from its tag, returning `None` if the tag is not `FAMC` or `FAMS`
     #[must_use]
-    pub fn new(xref: Xref, tag: &str) -> FamilyLink {
+    pub fn new(xref: Xref, tag: &str) -> Option<FamilyLink> {
         let link_type = match tag {
             "FAMC" => FamilyLinkType::Child,
             "FAMS" => FamilyLinkType::Spouse,
-            _ => panic!("Unrecognized family type tag: {}", tag),
+            _ => return None,
         };
-        FamilyLink(xref, link_type, None)
+        Some(FamilyLink(xref, link_type, None, Vec::new()))
     }
 
-    pub fn set_pedigree(&mut self, pedigree_text: &str) {
+    /// Sets the pedigree of the link.
+    /// Returns `false` without changing the link if the text is not a known pedigree.
+    pub fn set_pedigree(&mut self, pedigree_text: &str) -> bool {
         self.2 = match pedigree_text.to_lowercase().as_str() {
             "adopted" => Some(Pedigree::Adopted),
             "birth" => Some(Pedigree::Birth),
             "foster" => Some(Pedigree::Foster),
             "sealing" => Some(Pedigree::Sealing),
-            _ => panic!("Unrecognized family link pedigree: {}", pedigree_text),
+            _ => return false,
         };
+        true
     }
 }
 
@@ -96,4 +135,5 @@ pub struct Name {
     pub value: Option<String>,
     pub given: Option<String>,
     pub surname: Option<String>,
+    pub extensions: Vec<GedcomNode>,
 }
@@ -0,0 +1,86 @@
+use crate::types::{NoteRef, SourceCitation};
+
+type Xref = String;
+
+/// Kind of an ordinance of the Church of Jesus Christ of Latter-day Saints
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LdsOrdinanceType {
+    /// Baptism (`BAPL`)
+    Baptism,
+    /// Confirmation (`CONL`)
+    Confirmation,
+    /// Endowment (`ENDL`)
+    Endowment,
+    /// Initiatory, introduced by GEDCOM 7.0 (`INIL`)
+    Initiatory,
+    /// Sealing of a child to their parents (`SLGC`)
+    ChildSealing,
+    /// Sealing of a couple (`SLGS`)
+    SpouseSealing,
+}
+
+impl LdsOrdinanceType {
+    /// The ordinance of a tag, or `None` if the tag is not an ordinance
+    #[must_use]
+    pub fn from_tag(tag: &str) -> Option<LdsOrdinanceType> {
+        let ordinance = match tag {
+            "BAPL" => LdsOrdinanceType::Baptism,
+            "CONL" => LdsOrdinanceType::Confirmation,
+            "ENDL" => LdsOrdinanceType::Endowment,
+            "INIL" => LdsOrdinanceType::Initiatory,
+            "SLGC" => LdsOrdinanceType::ChildSealing,
+            "SLGS" => LdsOrdinanceType::SpouseSealing,
+            _ => return None,
+        };
+        Some(ordinance)
+    }
+
+    /// The tag of the ordinance
+    #[must_use]
+    pub fn tag(self) -> &'static str {
+        match self {
+            LdsOrdinanceType::Baptism => "BAPL",
+            LdsOrdinanceType::Confirmation => "CONL",
+            LdsOrdinanceType::Endowment => "ENDL",
+            LdsOrdinanceType::Initiatory => "INIL",
+            LdsOrdinanceType::ChildSealing => "SLGC",
+            LdsOrdinanceType::SpouseSealing => "SLGS",
+        }
+    }
+}
+
+/// Ordinance of the Church of Jesus Christ of Latter-day Saints performed for an individual or
+/// a couple
+#[derive(Clone, Debug)]
+pub struct LdsOrdinance {
+    pub ordinance: LdsOrdinanceType,
+    pub date: Option<String>,
+    /// Temple the ordinance was performed in, the `TEMP` tag
+    pub temple: Option<String>,
+    pub place: Option<String>,
+    /// Status of the ordinance, _ie._ `COMPLETED` (`STAT`)
+    pub status: Option<String>,
+    /// When the status was given, the `DATE` tag under `STAT`
+    pub status_date: Option<String>,
+    /// Family the child is sealed to, the `FAMC` tag of a child sealing
+    pub family: Option<Xref>,
+    pub citations: Vec<SourceCitation>,
+    pub notes: Vec<NoteRef>,
+}
+
+impl LdsOrdinance {
+    #[must_use]
+    pub fn new(ordinance: LdsOrdinanceType) -> LdsOrdinance {
+        LdsOrdinance {
+            ordinance,
+            date: None,
+            temple: None,
+            place: None,
+            status: None,
+            status_date: None,
+            family: None,
+            citations: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+}
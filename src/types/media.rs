@@ -0,0 +1,94 @@
+use crate::types::{ChangeDate, ExternalId, NoteRef, SourceCitation, Translation, UserReference};
+use crate::GedcomNode;
+
+type Xref = String;
+
+/// Multimedia record (`OBJE`), files such as photos or documents linked to facts
+#[derive(Debug)]
+pub struct Media {
+    /// Optional reference to link to this multimedia record
+    pub xref: Option<Xref>,
+    /// References to the files of the multimedia record
+    pub files: Vec<MediaFile>,
+    /// User-defined reference numbers (`REFN`)
+    pub user_references: Vec<UserReference>,
+    /// Record id assigned by the program that wrote the file (`RIN`)
+    pub automated_record_id: Option<String>,
+    /// Identifiers of the record in other systems (`EXID`)
+    pub external_ids: Vec<ExternalId>,
+    /// Globally unique identifiers of the record (`UID`)
+    pub unique_ids: Vec<String>,
+    pub notes: Vec<NoteRef>,
+    pub citations: Vec<SourceCitation>,
+    /// When the record was last changed (`CHAN`)
+    pub change_date: Option<ChangeDate>,
+    /// User-defined tags and their substructures
+    pub extensions: Vec<GedcomNode>,
+    pub(crate) node: Option<GedcomNode>,
+}
+
+parsed_record!(Media);
+
+impl Media {
+    /// Shorthand for creating a `Media` from its `xref`
+    #[must_use]
+    pub fn new(xref: Option<Xref>) -> Media {
+        Media {
+            xref,
+            files: Vec::new(),
+            user_references: Vec::new(),
+            automated_record_id: None,
+            external_ids: Vec::new(),
+            unique_ids: Vec::new(),
+            notes: Vec::new(),
+            citations: Vec::new(),
+            change_date: None,
+            extensions: Vec::new(),
+            node: None,
+        }
+    }
+}
+
+/// Reference to a multimedia file, the `FILE` tag
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MediaFile {
+    /// Path or URL of the file
+    pub value: Option<String>,
+    /// Format of the file, _ie._ `jpeg`
+    pub form: Option<String>,
+    /// Type of the source the file was made from, _ie._ `photo` (`TYPE` or `MEDI`)
+    pub media_type: Option<String>,
+    /// Descriptive title of the file
+    pub title: Option<String>,
+    /// Paths of other versions of the file, _ie._ a transcript (`TRAN`)
+    pub translations: Vec<Translation>,
+}
+
+/// Multimedia linked to a record or structure, either pointing to a `Media` record or written
+/// in place
+#[derive(Clone, Debug, PartialEq)]
+pub enum MediaLink {
+    /// Reference to a `Media` record
+    Pointer(Xref),
+    /// Files of the multimedia, written in place
+    Inline {
+        files: Vec<MediaFile>,
+        title: Option<String>,
+    },
+    /// Reference to a `Media` record along with the region of it to show and a title, the
+    /// `CROP` and `TITL` tags of GEDCOM 7.0
+    Reference {
+        xref: Xref,
+        crop: Option<Crop>,
+        title: Option<String>,
+    },
+}
+
+/// Region of an image to show, in pixels from its top left corner, the `CROP` tag
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Crop {
+    pub top: Option<u32>,
+    pub left: Option<u32>,
+    pub height: Option<u32>,
+    pub width: Option<u32>,
+}
@@ -9,9 +9,17 @@ pub use event::*;
 mod address;
 pub use address::*;
 
+use crate::GedcomNode;
+
 type Xref = String;
 
 // top-level record types
+mod header;
+pub use header::*;
+
+mod note;
+pub use note::*;
+
 mod individual;
 pub use individual::*;
 
@@ -21,13 +29,17 @@ pub use family::*;
 mod submitter;
 pub use submitter::*;
 
+mod submission;
+pub use submission::*;
+
 mod source;
 pub use source::*;
 
-// TODO
-/// Multimedia item
-#[derive(Debug)]
-pub struct Media {}
+mod media;
+pub use media::*;
+
+mod lds;
+pub use lds::*;
 
 /// Data repository, the `REPO` tag
 #[derive(Debug)]
@@ -38,6 +50,34 @@ pub struct Repository {
     pub name: Option<String>,
     /// Physical address of the data repository
     pub address: Option<Address>,
+    /// Notes about the repository
+    pub notes: Vec<NoteRef>,
+    /// Identifiers of the record in other systems (`EXID`)
+    pub external_ids: Vec<ExternalId>,
+    /// Globally unique identifiers of the record (`UID`)
+    pub unique_ids: Vec<String>,
+    /// User-defined tags and their substructures
+    pub extensions: Vec<GedcomNode>,
+    pub(crate) node: Option<GedcomNode>,
+}
+
+parsed_record!(Repository);
+
+impl Repository {
+    /// Shorthand for creating a `Repository` from its `xref`
+    #[must_use]
+    pub fn new(xref: Option<Xref>) -> Repository {
+        Repository {
+            xref,
+            name: None,
+            address: None,
+            notes: Vec::new(),
+            external_ids: Vec::new(),
+            unique_ids: Vec::new(),
+            extensions: Vec::new(),
+            node: None,
+        }
+    }
 }
 
 /// Citation linking a genealogy fact to a data `Source`
@@ -47,6 +87,12 @@ pub struct SourceCitation {
     pub xref: Xref,
     /// Page number of source
     pub page: Option<String>,
+    /// Notes about the citation
+    pub notes: Vec<NoteRef>,
+    /// Multimedia linked to the citation
+    pub multimedia: Vec<MediaLink>,
+    /// User-defined tags and their substructures
+    pub extensions: Vec<GedcomNode>,
 }
 
 /// Citation linking a `Source` to a data `Repository`
@@ -56,4 +102,6 @@ pub struct RepoCitation {
     pub xref: Xref,
     /// Call number to find the source at this repository
     pub call_number: Option<String>,
+    /// User-defined tags and their substructures
+    pub extensions: Vec<GedcomNode>,
 }
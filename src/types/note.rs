@@ -0,0 +1,115 @@
+use crate::types::SourceCitation;
+use crate::GedcomNode;
+
+type Xref = String;
+
+/// Note record (`NOTE`), text shared between the records that point to it
+#[derive(Debug)]
+pub struct Note {
+    /// Optional reference to link to this note
+    pub xref: Option<Xref>,
+    /// Text of the note
+    pub value: Option<String>,
+    /// Media type of the text, _ie._ `text/html` (`MIME`)
+    pub mime: Option<String>,
+    /// Language of the text (`LANG`)
+    pub language: Option<String>,
+    /// The text in other languages or media types (`TRAN`)
+    pub translations: Vec<Translation>,
+    /// Sources of the note's text
+    pub citations: Vec<SourceCitation>,
+    /// User-defined reference numbers (`REFN`)
+    pub user_references: Vec<UserReference>,
+    /// Record id assigned by the program that wrote the file (`RIN`)
+    pub automated_record_id: Option<String>,
+    /// Identifiers of the note in other systems (`EXID`)
+    pub external_ids: Vec<ExternalId>,
+    /// Globally unique identifiers of the note (`UID`)
+    pub unique_ids: Vec<String>,
+    /// When the note was last changed (`CHAN`)
+    pub change_date: Option<ChangeDate>,
+    /// User-defined tags and their substructures
+    pub extensions: Vec<GedcomNode>,
+    pub(crate) node: Option<GedcomNode>,
+}
+
+parsed_record!(Note);
+
+impl Note {
+    /// Shorthand for creating a `Note` from its `xref`
+    #[must_use]
+    pub fn new(xref: Option<Xref>) -> Note {
+        Note {
+            xref,
+            value: None,
+            mime: None,
+            language: None,
+            translations: Vec::new(),
+            citations: Vec::new(),
+            user_references: Vec::new(),
+            automated_record_id: None,
+            external_ids: Vec::new(),
+            unique_ids: Vec::new(),
+            change_date: None,
+            extensions: Vec::new(),
+            node: None,
+        }
+    }
+
+    pub fn add_citation(&mut self, citation: SourceCitation) {
+        self.citations.push(citation);
+    }
+}
+
+/// A note attached to a record or structure, either written in place or pointing to a `Note`
+#[derive(Clone, Debug, PartialEq)]
+pub enum NoteRef {
+    /// Text of the note, written in place
+    Text(String),
+    /// Reference to a `Note` record
+    Pointer(Xref),
+}
+
+/// Whether a value reads as a pointer to a record rather than as text
+pub(crate) fn is_pointer(value: &str) -> bool {
+    value.len() > 2
+        && value.starts_with('@')
+        && value.ends_with('@')
+        && !value.starts_with("@@")
+        && !value.contains(char::is_whitespace)
+}
+
+/// User-defined reference number of a record, the `REFN` tag
+#[derive(Debug, Default)]
+pub struct UserReference {
+    pub value: Option<String>,
+    /// User-defined type of the reference, the `TYPE` tag
+    pub reference_type: Option<String>,
+}
+
+/// Identifier of a record in another system, the `EXID` tag of GEDCOM 7.0
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExternalId {
+    pub value: Option<String>,
+    /// URI of the system that issued the identifier, the `TYPE` tag
+    pub id_type: Option<String>,
+}
+
+/// Text in another language or media type, the `TRAN` tag of GEDCOM 7.0
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Translation {
+    pub value: Option<String>,
+    /// Language of the text (`LANG`)
+    pub language: Option<String>,
+    /// Media type of the text, or of the file a file path translates to (`MIME` or `FORM`)
+    pub mime: Option<String>,
+}
+
+/// When a record was last changed, the `CHAN` tag
+#[derive(Debug, Default)]
+pub struct ChangeDate {
+    pub date: Option<String>,
+    /// Time of the change, the `TIME` tag under `DATE`
+    pub time: Option<String>,
+    pub notes: Vec<NoteRef>,
+}
@@ -1,4 +1,5 @@
-use crate::types::{Event, RepoCitation};
+use crate::types::{Event, ExternalId, MediaLink, NoteRef, RepoCitation};
+use crate::GedcomNode;
 
 #[derive(Debug)]
 /// Source for genealogy facts
@@ -8,8 +9,18 @@ pub struct Source {
     pub abbreviation: Option<String>,
     pub title: Option<String>,
     repo_citations: Vec<RepoCitation>,
+    pub notes: Vec<NoteRef>,
+    pub multimedia: Vec<MediaLink>,
+    /// Identifiers of the record in other systems (`EXID`)
+    pub external_ids: Vec<ExternalId>,
+    /// Globally unique identifiers of the record (`UID`)
+    pub unique_ids: Vec<String>,
+    pub extensions: Vec<GedcomNode>,
+    pub(crate) node: Option<GedcomNode>,
 }
 
+parsed_record!(Source);
+
 impl Source {
     #[must_use]
     pub fn new(xref: Option<String>) -> Source {
@@ -22,12 +33,24 @@ impl Source {
             abbreviation: None,
             title: None,
             repo_citations: Vec::new(),
+            notes: Vec::new(),
+            multimedia: Vec::new(),
+            external_ids: Vec::new(),
+            unique_ids: Vec::new(),
+            extensions: Vec::new(),
+            node: None,
         }
     }
 
     pub fn add_repo_citation(&mut self, citation: RepoCitation) {
         self.repo_citations.push(citation);
     }
+
+    /// The repositories holding the source
+    #[must_use]
+    pub fn repo_citations(&self) -> &[RepoCitation] {
+        &self.repo_citations
+    }
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -41,4 +64,15 @@ impl SourceData {
     pub fn add_event(&mut self, event: Event) {
         self.events.push(event);
     }
+
+    /// The events recorded by the source, the `EVEN` tags of its `DATA`
+    #[must_use]
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// The events recorded by the source, to change them in place
+    pub fn events_mut(&mut self) -> &mut [Event] {
+        &mut self.events
+    }
 }
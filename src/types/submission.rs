@@ -0,0 +1,45 @@
+use crate::types::{ChangeDate, NoteRef};
+use crate::GedcomNode;
+
+type Xref = String;
+
+/// Submission record (`SUBN`), describing how a file is to be processed by the Family History
+/// Department of the LDS church
+#[derive(Debug, Default)]
+pub struct Submission {
+    /// Optional reference to link to this submission, from the header's `SUBN`
+    pub xref: Option<Xref>,
+    /// Pointer to the `Submitter` of the submission
+    pub submitter: Option<Xref>,
+    /// Name of the family file (`FAMF`)
+    pub family_file: Option<String>,
+    /// Temple code of the temple receiving the submission (`TEMP`)
+    pub temple_code: Option<String>,
+    /// Generations of ancestors to include (`ANCE`)
+    pub ancestor_generations: Option<String>,
+    /// Generations of descendants to include (`DESC`)
+    pub descendant_generations: Option<String>,
+    /// Whether temple ordinances should be processed, `yes` or `no` (`ORDI`)
+    pub ordinance_process_flag: Option<String>,
+    /// Record id assigned by the program that wrote the file (`RIN`)
+    pub automated_record_id: Option<String>,
+    pub notes: Vec<NoteRef>,
+    /// When the record was last changed (`CHAN`)
+    pub change_date: Option<ChangeDate>,
+    /// User-defined tags and their substructures
+    pub extensions: Vec<GedcomNode>,
+    pub(crate) node: Option<GedcomNode>,
+}
+
+parsed_record!(Submission);
+
+impl Submission {
+    /// Shorthand for creating a `Submission` from its `xref`
+    #[must_use]
+    pub fn new(xref: Option<Xref>) -> Submission {
+        Submission {
+            xref,
+            ..Submission::default()
+        }
+    }
+}
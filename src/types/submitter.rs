@@ -1,4 +1,5 @@
-use crate::types::Address;
+use crate::types::{Address, ExternalId, NoteRef};
+use crate::GedcomNode;
 
 type Xref = String;
 
@@ -13,8 +14,21 @@ pub struct Submitter {
     pub address: Option<Address>,
     /// Phone number of the submitter
     pub phone: Option<String>,
+    /// Notes about the submitter
+    pub notes: Vec<NoteRef>,
+    /// Identifiers of the record in other systems (`EXID`)
+    pub external_ids: Vec<ExternalId>,
+    /// Globally unique identifiers of the record (`UID`)
+    pub unique_ids: Vec<String>,
+    /// Permanent record file number of the submitter, the `RFN` tag of GEDCOM 5.5.1
+    pub record_file_number: Option<String>,
+    /// User-defined tags and their substructures
+    pub extensions: Vec<GedcomNode>,
+    pub(crate) node: Option<GedcomNode>,
 }
 
+parsed_record!(Submitter);
+
 impl Submitter {
     /// Shorthand for creating a `Submitter` from its `xref`
     #[must_use]
@@ -24,6 +38,12 @@ impl Submitter {
             name: None,
             address: None,
             phone: None,
+            notes: Vec::new(),
+            external_ids: Vec::new(),
+            unique_ids: Vec::new(),
+            record_file_number: None,
+            extensions: Vec::new(),
+            node: None,
         }
     }
 }
@@ -9,3 +9,23 @@ macro_rules! fmt_optional_value {
         }
     };
 }
+
+/// Macro for the accessors of a typed record to the lines it was parsed from, held in its
+/// `node` field.
+macro_rules! parsed_record {
+    ($record: ident) => {
+        impl $record {
+            /// The raw line and substructures the record was parsed from
+            #[must_use]
+            pub fn node(&self) -> Option<&$crate::GedcomNode> {
+                self.node.as_ref()
+            }
+
+            /// The location of the lines the record was parsed from
+            #[must_use]
+            pub fn span(&self) -> Option<$crate::Span> {
+                self.node.as_ref().map(|node| node.span)
+            }
+        }
+    };
+}
@@ -0,0 +1,772 @@
+//! Writes `GedcomData` back to GEDCOM 5.5.1 or 7.0 text
+use std::borrow::Cow;
+use std::iter;
+
+use crate::encoding::{self, Charset, FileEncoding};
+use crate::error::GedcomError;
+use crate::tree::GedcomData;
+use crate::types::{
+    is_pointer, Address, AdoptedBy, ChangeDate, Crop, Event, EventType, ExternalId, Family,
+    FamilyLink, FamilyLinkType, GedcomVersion, Gender, Header, Individual, LdsOrdinance, Media,
+    MediaFile, MediaLink, NonEvent, Note, NoteRef, Pedigree, Repository, Source, SourceCitation,
+    Submission, Submitter, Translation, UserReference,
+};
+use crate::{GedcomNode, Record};
+
+/// The most characters a GEDCOM 5.5.1 line may hold, counting its level, tag, value and
+/// terminator
+const MAX_LINE_LENGTH: usize = 255;
+
+/// The characters ending each line
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineTerminator {
+    /// Line feed, `\n`
+    #[default]
+    Lf,
+    /// Carriage return followed by a line feed, `\r\n`
+    CrLf,
+    /// Carriage return, `\r`
+    Cr,
+    /// Line feed followed by a carriage return, `\n\r`
+    LfCr,
+}
+
+impl LineTerminator {
+    /// The characters of the terminator
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineTerminator::Lf => "\n",
+            LineTerminator::CrLf => "\r\n",
+            LineTerminator::Cr => "\r",
+            LineTerminator::LfCr => "\n\r",
+        }
+    }
+}
+
+/// Options that control how a file is written
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WriterOptions {
+    /// The characters ending each line
+    pub terminator: LineTerminator,
+    /// The character set the file is encoded with, which the `CHAR` tag of the header names,
+    /// along with its byte order mark and byte order. `Parser::encoding` gives those of a file
+    /// that was read. GEDCOM 7.0 files are always UTF-8 and have no `CHAR` tag.
+    pub encoding: FileEncoding,
+    /// The version of the specification the file is written in, see `convert::convert` to
+    /// change the structures of the data to the version
+    pub version: GedcomVersion,
+}
+
+/// Writes GEDCOM 5.5.1 or 7.0 text from the typed records
+///
+/// Values spanning several lines are split into `CONT` lines. In GEDCOM 5.5.1 values longer
+/// than a line allows are split into `CONC` lines and an `@` in text is doubled, while the
+/// values of pointer fields and escape sequences such as `@#DJULIAN@` are written as they are.
+/// GEDCOM 7.0 has no line length limit and only doubles an `@` starting a value, and its shared
+/// notes are `SNOTE`.
+///
+/// ```rust
+/// use gedcom::parser::Parser;
+/// use gedcom::writer::GedcomWriter;
+///
+/// let source = std::fs::read_to_string("./tests/fixtures/sample.ged").unwrap();
+/// let data = Parser::new(&source).parse_record().unwrap();
+///
+/// let mut writer = GedcomWriter::new();
+/// writer.write_data(&data);
+/// let bytes = writer.finish().unwrap();
+/// ```
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Default)]
+pub struct GedcomWriter {
+    options: WriterOptions,
+    text: String,
+}
+
+impl GedcomWriter {
+    /// Creates a writer of UTF-8 text with `LF` terminators
+    #[must_use]
+    pub fn new() -> GedcomWriter {
+        GedcomWriter::default()
+    }
+
+    /// Creates a writer with the given `WriterOptions`
+    #[must_use]
+    pub fn with_options(options: WriterOptions) -> GedcomWriter {
+        GedcomWriter {
+            options,
+            text: String::new(),
+        }
+    }
+
+    /// Writes a whole file: the header, every record and the trailer. A default header is
+    /// written if the data has none. Records are written from their fields rather than the
+    /// nodes they were parsed from, so use `write_tree` to write a file back as it was read.
+    pub fn write_data(&mut self, data: &GedcomData) {
+        let default_header = Header::default();
+        let header = data.header.as_ref().unwrap_or(&default_header);
+        // the specification requires the header to point to a submitter
+        let submitter = (data.submitters.first()).and_then(|submitter| submitter.xref.as_deref());
+        self.write_header(header, submitter);
+
+        if let Some(submission) = &data.submission {
+            self.write_submission(submission);
+        }
+        for submitter in &data.submitters {
+            self.write_submitter(submitter);
+        }
+        for individual in &data.individuals {
+            self.write_individual(individual);
+        }
+        for family in &data.families {
+            self.write_family(family);
+        }
+        for repository in &data.repositories {
+            self.write_repository(repository);
+        }
+        for source in &data.sources {
+            self.write_source(source);
+        }
+        for media in &data.multimedia {
+            self.write_media(media);
+        }
+        for note in &data.notes {
+            self.write_note(note);
+        }
+        for node in &data.extensions {
+            self.write_node(0, node);
+        }
+        self.write_line(0, None, "TRLR", None);
+    }
+
+    /// Writes a single record, so that records can be written as they are parsed. The file
+    /// starts with the header and ends with the trailer.
+    pub fn write_record(&mut self, record: &Record) {
+        match record {
+            Record::Header(header) => self.write_header(header, None),
+            Record::Submitter(submitter) => self.write_submitter(submitter),
+            Record::Submission(submission) => self.write_submission(submission),
+            Record::Individual(individual) => self.write_individual(individual),
+            Record::Family(family) => self.write_family(family),
+            Record::Source(source) => self.write_source(source),
+            Record::Repository(repository) => self.write_repository(repository),
+            Record::Note(note) => self.write_note(note),
+            Record::Media(media) => self.write_media(media),
+            Record::Extension(node) => self.write_node(0, node),
+            Record::Trailer => self.write_line(0, None, "TRLR", None),
+        }
+    }
+
+    /// Writes nodes and the nodes beneath them at the levels they hold, such as the level-0
+    /// nodes of `Parser::parse_tree`. Lines parsed with `ParserOptions::lossless` are written
+    /// exactly as they were read, so a tree that was not changed is written back to the same
+    /// text regardless of the options.
+    pub fn write_tree(&mut self, nodes: &[GedcomNode]) {
+        for node in nodes {
+            if let Some(raw) = &node.raw {
+                self.text.push_str(raw);
+            } else {
+                self.write_node_line(node.level, node);
+            }
+            self.write_tree(&node.children);
+        }
+    }
+
+    /// The text written so far
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// The text written encoded with the encoding of the options, or as UTF-8 for GEDCOM 7.0
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GedcomError` if the text has a character the character set cannot write.
+    pub fn finish(self) -> Result<Vec<u8>, GedcomError> {
+        let encoding = match self.options.version {
+            GedcomVersion::V7 if self.options.encoding.charset != Charset::Utf8 => {
+                FileEncoding::from(Charset::Utf8)
+            }
+            _ => self.options.encoding,
+        };
+        encoding::encode(&self.text, encoding)
+    }
+
+    /// Writes the header, which declares the version and, for GEDCOM 5.5.1, the character set of
+    /// the options. `submitter` is pointed to when the header does not point to one.
+    fn write_header(&mut self, header: &Header, submitter: Option<&str>) {
+        self.write_line(0, None, "HEAD", None);
+
+        if let Some(source) = &header.source {
+            self.write_line(1, None, "SOUR", source.value.as_deref());
+            self.write_value(2, "VERS", source.version.as_deref());
+            self.write_value(2, "NAME", source.name.as_deref());
+            if let Some(corporation) = &source.corporation {
+                self.write_line(2, None, "CORP", corporation.value.as_deref());
+                if let Some(address) = &corporation.address {
+                    self.write_address(3, address);
+                }
+                self.write_value(3, "PHON", corporation.phone.as_deref());
+                self.write_value(3, "EMAIL", corporation.email.as_deref());
+                self.write_value(3, "FAX", corporation.fax.as_deref());
+                self.write_value(3, "WWW", corporation.website.as_deref());
+            }
+            if let Some(data) = &source.data {
+                self.write_line(2, None, "DATA", data.value.as_deref());
+                self.write_value(3, "DATE", data.date.as_deref());
+                self.write_value(3, "COPR", data.copyright.as_deref());
+            }
+        } else {
+            self.write_line(1, None, "SOUR", Some(env!("CARGO_PKG_NAME")));
+            self.write_line(2, None, "VERS", Some(env!("CARGO_PKG_VERSION")));
+        }
+        self.write_value(1, "DEST", header.destination.as_deref());
+        if let Some(date) = &header.date {
+            self.write_line(1, None, "DATE", date.value.as_deref());
+            self.write_value(2, "TIME", date.time.as_deref());
+        }
+        self.write_pointer(1, "SUBM", header.submitter_tag.as_deref().or(submitter));
+        self.write_pointer(1, "SUBN", header.submission_tag.as_deref());
+        self.write_value(1, "FILE", header.filename.as_deref());
+        self.write_value(1, "COPR", header.copyright.as_deref());
+        self.write_line(1, None, "GEDC", None);
+        if self.options.version == GedcomVersion::V7 {
+            self.write_line(2, None, "VERS", Some("7.0"));
+        } else {
+            self.write_line(2, None, "VERS", Some("5.5.1"));
+            self.write_line(2, None, "FORM", Some("LINEAGE-LINKED"));
+            let charset = self.options.encoding.charset;
+            self.write_line(1, None, "CHAR", Some(charset.name()));
+        }
+        if !header.schema.is_empty() {
+            self.write_line(1, None, "SCHMA", None);
+            for schema_tag in &header.schema {
+                let value = format!("{} {}", schema_tag.tag, schema_tag.uri);
+                self.write_line(2, None, "TAG", Some(&value));
+            }
+        }
+        self.write_value(1, "LANG", header.language.as_deref());
+        if let Some(place) = &header.place {
+            self.write_line(1, None, "PLAC", None);
+            self.write_value(2, "FORM", place.form.as_deref());
+        }
+        self.write_value(1, "NOTE", header.note.as_deref());
+        self.write_nodes(1, &header.extensions);
+    }
+
+    fn write_submitter(&mut self, submitter: &Submitter) {
+        self.write_line(0, submitter.xref.as_deref(), "SUBM", None);
+        self.write_value(1, "NAME", submitter.name.as_deref());
+        if let Some(address) = &submitter.address {
+            self.write_address(1, address);
+        }
+        self.write_value(1, "PHON", submitter.phone.as_deref());
+        self.write_value(1, "RFN", submitter.record_file_number.as_deref());
+        self.write_identifiers(&submitter.external_ids, &submitter.unique_ids);
+        self.write_note_refs(1, &submitter.notes);
+        self.write_nodes(1, &submitter.extensions);
+    }
+
+    fn write_submission(&mut self, submission: &Submission) {
+        self.write_line(0, submission.xref.as_deref(), "SUBN", None);
+        self.write_pointer(1, "SUBM", submission.submitter.as_deref());
+        self.write_value(1, "FAMF", submission.family_file.as_deref());
+        self.write_value(1, "TEMP", submission.temple_code.as_deref());
+        self.write_value(1, "ANCE", submission.ancestor_generations.as_deref());
+        self.write_value(1, "DESC", submission.descendant_generations.as_deref());
+        self.write_value(1, "ORDI", submission.ordinance_process_flag.as_deref());
+        self.write_value(1, "RIN", submission.automated_record_id.as_deref());
+        self.write_note_refs(1, &submission.notes);
+        if let Some(change_date) = &submission.change_date {
+            self.write_change_date(1, change_date);
+        }
+        self.write_nodes(1, &submission.extensions);
+    }
+
+    fn write_individual(&mut self, individual: &Individual) {
+        self.write_line(0, individual.xref.as_deref(), "INDI", None);
+        if let Some(name) = &individual.name {
+            self.write_line(1, None, "NAME", name.value.as_deref());
+            self.write_value(2, "GIVN", name.given.as_deref());
+            self.write_value(2, "SURN", name.surname.as_deref());
+            self.write_nodes(2, &name.extensions);
+        }
+        let sex = match individual.sex {
+            Gender::Male => Some("M"),
+            Gender::Female => Some("F"),
+            Gender::Nonbinary if self.options.version == GedcomVersion::V7 => Some("X"),
+            Gender::Nonbinary => Some("N"),
+            Gender::Unknown => None,
+        };
+        self.write_value(1, "SEX", sex);
+        for event in &individual.events {
+            self.write_event(1, event);
+        }
+        for non_event in &individual.non_events {
+            self.write_non_event(non_event);
+        }
+        for ordinance in &individual.lds_ordinances {
+            self.write_lds_ordinance(ordinance);
+        }
+        for link in &individual.families {
+            self.write_family_link(link);
+        }
+        self.write_value(1, "RFN", individual.record_file_number.as_deref());
+        self.write_identifiers(&individual.external_ids, &individual.unique_ids);
+        self.write_note_refs(1, &individual.notes);
+        self.write_media_links(1, &individual.multimedia);
+        self.write_nodes(1, &individual.extensions);
+    }
+
+    fn write_family_link(&mut self, link: &FamilyLink) {
+        let FamilyLink(xref, link_type, pedigree, extensions) = link;
+        let tag = match link_type {
+            FamilyLinkType::Child => "FAMC",
+            FamilyLinkType::Spouse => "FAMS",
+        };
+        self.write_pointer(1, tag, Some(xref));
+        let pedigree = pedigree.as_ref().map(|pedigree| match pedigree {
+            Pedigree::Adopted => "adopted",
+            Pedigree::Birth => "birth",
+            Pedigree::Foster => "foster",
+            Pedigree::Sealing => "sealing",
+        });
+        self.write_value(2, "PEDI", pedigree);
+        self.write_nodes(2, extensions);
+    }
+
+    fn write_family(&mut self, family: &Family) {
+        self.write_line(0, family.xref.as_deref(), "FAM", None);
+        for event in family.events() {
+            self.write_event(1, event);
+        }
+        for non_event in &family.non_events {
+            self.write_non_event(non_event);
+        }
+        self.write_pointer(1, "HUSB", family.individual1.as_deref());
+        self.write_pointer(1, "WIFE", family.individual2.as_deref());
+        for child in &family.children {
+            self.write_pointer(1, "CHIL", Some(child));
+        }
+        for ordinance in &family.lds_ordinances {
+            self.write_lds_ordinance(ordinance);
+        }
+        if let Some(num_children) = family.num_children {
+            self.write_line(1, None, "NCHI", Some(&num_children.to_string()));
+        }
+        self.write_identifiers(&family.external_ids, &family.unique_ids);
+        self.write_note_refs(1, &family.notes);
+        self.write_media_links(1, &family.multimedia);
+        self.write_nodes(1, &family.extensions);
+    }
+
+    fn write_event(&mut self, level: u8, event: &Event) {
+        let value = match &event.event {
+            EventType::SourceData(recorded) => Some(recorded.as_str()),
+            _ => event.value.as_deref(),
+        };
+        self.write_line(level, None, event.event.tag(), value);
+        self.write_value(level + 1, "TYPE", event.event_type.as_deref());
+        let date_phrase = event.date_phrase.as_deref();
+        self.write_date(level + 1, event.date.as_deref(), date_phrase);
+        self.write_value(level + 1, "SDATE", event.sort_date.as_deref());
+        self.write_value(level + 1, "PLAC", event.place.as_deref());
+        if let Some(family) = &event.family {
+            self.write_pointer(level + 1, "FAMC", Some(family));
+            let adopted_by = event
+                .adopted_by
+                .as_ref()
+                .map(|adopted_by| match adopted_by {
+                    AdoptedBy::Husband => "HUSB",
+                    AdoptedBy::Wife => "WIFE",
+                    AdoptedBy::Both => "BOTH",
+                });
+            self.write_value(level + 2, "ADOP", adopted_by);
+        }
+        for (tag, age) in [("HUSB", &event.husband_age), ("WIFE", &event.wife_age)] {
+            if let Some(age) = age {
+                self.write_line(level + 1, None, tag, None);
+                self.write_line(level + 2, None, "AGE", Some(age));
+            }
+        }
+        self.write_citations(level + 1, &event.citations);
+        self.write_note_refs(level + 1, &event.notes);
+        self.write_media_links(level + 1, &event.multimedia);
+        self.write_nodes(level + 1, &event.extensions);
+    }
+
+    fn write_non_event(&mut self, non_event: &NonEvent) {
+        self.write_line(1, None, "NO", Some(non_event.event.tag()));
+        let date_phrase = non_event.date_phrase.as_deref();
+        self.write_date(2, non_event.date.as_deref(), date_phrase);
+        self.write_citations(2, &non_event.citations);
+        self.write_note_refs(2, &non_event.notes);
+    }
+
+    fn write_lds_ordinance(&mut self, ordinance: &LdsOrdinance) {
+        self.write_line(1, None, ordinance.ordinance.tag(), None);
+        self.write_value(2, "DATE", ordinance.date.as_deref());
+        self.write_value(2, "TEMP", ordinance.temple.as_deref());
+        self.write_value(2, "PLAC", ordinance.place.as_deref());
+        self.write_pointer(2, "FAMC", ordinance.family.as_deref());
+        self.write_value(2, "STAT", ordinance.status.as_deref());
+        if ordinance.status.is_some() {
+            self.write_value(3, "DATE", ordinance.status_date.as_deref());
+        }
+        self.write_citations(2, &ordinance.citations);
+        self.write_note_refs(2, &ordinance.notes);
+    }
+
+    fn write_source(&mut self, source: &Source) {
+        self.write_line(0, source.xref.as_deref(), "SOUR", None);
+        let data = &source.data;
+        if !data.events().is_empty() || data.agency.is_some() {
+            self.write_line(1, None, "DATA", None);
+            for event in data.events() {
+                self.write_event(2, event);
+            }
+            self.write_value(2, "AGNC", data.agency.as_deref());
+        }
+        self.write_value(1, "TITL", source.title.as_deref());
+        self.write_value(1, "ABBR", source.abbreviation.as_deref());
+        for citation in source.repo_citations() {
+            self.write_pointer(1, "REPO", Some(&citation.xref));
+            self.write_value(2, "CALN", citation.call_number.as_deref());
+            self.write_nodes(2, &citation.extensions);
+        }
+        self.write_identifiers(&source.external_ids, &source.unique_ids);
+        self.write_note_refs(1, &source.notes);
+        self.write_media_links(1, &source.multimedia);
+        self.write_nodes(1, &source.extensions);
+    }
+
+    fn write_repository(&mut self, repository: &Repository) {
+        self.write_line(0, repository.xref.as_deref(), "REPO", None);
+        self.write_value(1, "NAME", repository.name.as_deref());
+        if let Some(address) = &repository.address {
+            self.write_address(1, address);
+        }
+        self.write_identifiers(&repository.external_ids, &repository.unique_ids);
+        self.write_note_refs(1, &repository.notes);
+        self.write_nodes(1, &repository.extensions);
+    }
+
+    fn write_note(&mut self, note: &Note) {
+        let tag = self.shared_note_tag();
+        self.write_line(0, note.xref.as_deref(), tag, note.value.as_deref());
+        self.write_value(1, "MIME", note.mime.as_deref());
+        self.write_value(1, "LANG", note.language.as_deref());
+        self.write_translations(1, &note.translations, "MIME");
+        self.write_citations(1, &note.citations);
+        self.write_user_references(&note.user_references);
+        self.write_value(1, "RIN", note.automated_record_id.as_deref());
+        self.write_identifiers(&note.external_ids, &note.unique_ids);
+        if let Some(change_date) = &note.change_date {
+            self.write_change_date(1, change_date);
+        }
+        self.write_nodes(1, &note.extensions);
+    }
+
+    fn write_media(&mut self, media: &Media) {
+        self.write_line(0, media.xref.as_deref(), "OBJE", None);
+        for file in &media.files {
+            self.write_media_file(1, file);
+        }
+        self.write_user_references(&media.user_references);
+        self.write_value(1, "RIN", media.automated_record_id.as_deref());
+        self.write_identifiers(&media.external_ids, &media.unique_ids);
+        self.write_note_refs(1, &media.notes);
+        self.write_citations(1, &media.citations);
+        if let Some(change_date) = &media.change_date {
+            self.write_change_date(1, change_date);
+        }
+        self.write_nodes(1, &media.extensions);
+    }
+
+    fn write_media_file(&mut self, level: u8, file: &MediaFile) {
+        self.write_line(level, None, "FILE", file.value.as_deref());
+        if let Some(form) = &file.form {
+            self.write_line(level + 1, None, "FORM", Some(form));
+            let tag = match self.options.version {
+                GedcomVersion::V5 => "TYPE",
+                GedcomVersion::V7 => "MEDI",
+            };
+            self.write_value(level + 2, tag, file.media_type.as_deref());
+        }
+        self.write_value(level + 1, "TITL", file.title.as_deref());
+        self.write_translations(level + 1, &file.translations, "FORM");
+    }
+
+    fn write_media_links(&mut self, level: u8, links: &[MediaLink]) {
+        for link in links {
+            match link {
+                MediaLink::Pointer(xref) => self.write_pointer(level, "OBJE", Some(xref)),
+                // 5.5.1 links to a record have no substructures
+                MediaLink::Reference { xref, .. } if self.options.version == GedcomVersion::V5 => {
+                    self.write_pointer(level, "OBJE", Some(xref));
+                }
+                MediaLink::Reference { xref, crop, title } => {
+                    self.write_pointer(level, "OBJE", Some(xref));
+                    if let Some(crop) = crop {
+                        self.write_crop(level + 1, crop);
+                    }
+                    self.write_value(level + 1, "TITL", title.as_deref());
+                }
+                MediaLink::Inline { files, title } => {
+                    self.write_line(level, None, "OBJE", None);
+                    for file in files {
+                        self.write_media_file(level + 1, file);
+                    }
+                    self.write_value(level + 1, "TITL", title.as_deref());
+                }
+            }
+        }
+    }
+
+    fn write_crop(&mut self, level: u8, crop: &Crop) {
+        self.write_line(level, None, "CROP", None);
+        let sides = [
+            ("TOP", crop.top),
+            ("LEFT", crop.left),
+            ("HEIGHT", crop.height),
+            ("WIDTH", crop.width),
+        ];
+        for (tag, pixels) in sides {
+            if let Some(pixels) = pixels {
+                self.write_line(level + 1, None, tag, Some(&pixels.to_string()));
+            }
+        }
+    }
+
+    fn write_citations(&mut self, level: u8, citations: &[SourceCitation]) {
+        for citation in citations {
+            // a citation without a source record describes it in text instead
+            if is_pointer(&citation.xref) {
+                self.write_pointer(level, "SOUR", Some(&citation.xref));
+            } else {
+                self.write_line(level, None, "SOUR", Some(&citation.xref));
+            }
+            self.write_value(level + 1, "PAGE", citation.page.as_deref());
+            self.write_note_refs(level + 1, &citation.notes);
+            self.write_media_links(level + 1, &citation.multimedia);
+            self.write_nodes(level + 1, &citation.extensions);
+        }
+    }
+
+    fn write_note_refs(&mut self, level: u8, notes: &[NoteRef]) {
+        for note in notes {
+            match note {
+                NoteRef::Text(text) => self.write_line(level, None, "NOTE", Some(text)),
+                NoteRef::Pointer(xref) => {
+                    let tag = self.shared_note_tag();
+                    self.write_pointer(level, tag, Some(xref));
+                }
+            }
+        }
+    }
+
+    /// The tag of a note record and of the pointers to it, `SNOTE` in GEDCOM 7.0
+    fn shared_note_tag(&self) -> &'static str {
+        match self.options.version {
+            GedcomVersion::V5 => "NOTE",
+            GedcomVersion::V7 => "SNOTE",
+        }
+    }
+
+    /// Writes translations of a text, whose media type is `MIME`, or of a file, whose media type
+    /// is `FORM`
+    fn write_translations(&mut self, level: u8, translations: &[Translation], mime_tag: &str) {
+        for translation in translations {
+            self.write_line(level, None, "TRAN", translation.value.as_deref());
+            self.write_value(level + 1, mime_tag, translation.mime.as_deref());
+            self.write_value(level + 1, "LANG", translation.language.as_deref());
+        }
+    }
+
+    fn write_identifiers(&mut self, external_ids: &[ExternalId], unique_ids: &[String]) {
+        for unique_id in unique_ids {
+            self.write_line(1, None, "UID", Some(unique_id));
+        }
+        for external_id in external_ids {
+            self.write_line(1, None, "EXID", external_id.value.as_deref());
+            self.write_value(2, "TYPE", external_id.id_type.as_deref());
+        }
+    }
+
+    /// Writes a date along with the phrase describing it, a GEDCOM 7.0 date may have only the
+    /// phrase
+    fn write_date(&mut self, level: u8, date: Option<&str>, phrase: Option<&str>) {
+        if date.is_some() || phrase.is_some() {
+            self.write_line(level, None, "DATE", date);
+            self.write_value(level + 1, "PHRASE", phrase);
+        }
+    }
+
+    fn write_user_references(&mut self, references: &[UserReference]) {
+        for reference in references {
+            self.write_line(1, None, "REFN", reference.value.as_deref());
+            self.write_value(2, "TYPE", reference.reference_type.as_deref());
+        }
+    }
+
+    fn write_change_date(&mut self, level: u8, change_date: &ChangeDate) {
+        self.write_line(level, None, "CHAN", None);
+        if let Some(date) = &change_date.date {
+            self.write_line(level + 1, None, "DATE", Some(date));
+            self.write_value(level + 2, "TIME", change_date.time.as_deref());
+        }
+        self.write_note_refs(level + 1, &change_date.notes);
+    }
+
+    fn write_address(&mut self, level: u8, address: &Address) {
+        self.write_line(level, None, "ADDR", address.value.as_deref());
+        self.write_value(level + 1, "ADR1", address.adr1.as_deref());
+        self.write_value(level + 1, "ADR2", address.adr2.as_deref());
+        self.write_value(level + 1, "ADR3", address.adr3.as_deref());
+        self.write_value(level + 1, "CITY", address.city.as_deref());
+        self.write_value(level + 1, "STAE", address.state.as_deref());
+        self.write_value(level + 1, "POST", address.post.as_deref());
+        self.write_value(level + 1, "CTRY", address.country.as_deref());
+    }
+
+    fn write_nodes(&mut self, level: u8, nodes: &[GedcomNode]) {
+        for node in nodes {
+            self.write_node(level, node);
+        }
+    }
+
+    /// Writes a node and the nodes beneath it at the given level, regardless of the level it was
+    /// parsed at
+    fn write_node(&mut self, level: u8, node: &GedcomNode) {
+        self.write_node_line(level, node);
+        self.write_nodes(level + 1, &node.children);
+    }
+
+    /// Writes the line of a node at the given level, its value as a pointer if it was parsed as
+    /// one
+    fn write_node_line(&mut self, level: u8, node: &GedcomNode) {
+        let xref = node.xref.as_deref();
+        match &node.value {
+            Some(pointer) if node.pointer => {
+                self.push_line(&line_prefix(level, xref, &node.tag), pointer);
+            }
+            value => self.write_line(level, xref, &node.tag, value.as_deref()),
+        }
+    }
+
+    /// Writes a line only if it has a value
+    fn write_value(&mut self, level: u8, tag: &str, value: Option<&str>) {
+        if value.is_some() {
+            self.write_line(level, None, tag, value);
+        }
+    }
+
+    /// Writes a line pointing to a record only if it has a pointer, which is not escaped
+    fn write_pointer(&mut self, level: u8, tag: &str, pointer: Option<&str>) {
+        if let Some(pointer) = pointer {
+            self.push_line(&line_prefix(level, None, tag), pointer);
+        }
+    }
+
+    /// Writes a line of text, continuing its value on `CONT` lines at each line break and, in
+    /// GEDCOM 5.5.1, on `CONC` lines where a line would be too long
+    fn write_line(&mut self, level: u8, xref: Option<&str>, tag: &str, value: Option<&str>) {
+        let mut prefix = line_prefix(level, xref, tag);
+
+        let Some(value) = value else {
+            self.push_line(&prefix, "");
+            return;
+        };
+        for (index, line) in value.split('\n').enumerate() {
+            if index > 0 {
+                prefix = format!("{} CONT", level + 1);
+            }
+            if self.options.version == GedcomVersion::V7 {
+                self.push_line(&prefix, &escape_leading(line));
+                continue;
+            }
+            let escaped = escape(line);
+            let mut rest: &str = &escaped;
+            loop {
+                // the value is preceded by a delimiter and followed by the terminator
+                let room = MAX_LINE_LENGTH.saturating_sub(
+                    prefix.chars().count() + 1 + self.options.terminator.as_str().len(),
+                );
+                let split = split_point(rest, room);
+                self.push_line(&prefix, &rest[..split]);
+                rest = &rest[split..];
+                if rest.is_empty() {
+                    break;
+                }
+                prefix = format!("{} CONC", level + 1);
+            }
+        }
+    }
+
+    fn push_line(&mut self, prefix: &str, value: &str) {
+        self.text.push_str(prefix);
+        if !value.is_empty() {
+            self.text.push(' ');
+            self.text.push_str(value);
+        }
+        self.text.push_str(self.options.terminator.as_str());
+    }
+}
+
+/// The level, cross-reference identifier and tag starting a line
+fn line_prefix(level: u8, xref: Option<&str>, tag: &str) -> String {
+    let mut prefix = level.to_string();
+    if let Some(xref) = xref {
+        prefix.push(' ');
+        prefix.push_str(xref);
+    }
+    prefix.push(' ');
+    prefix.push_str(tag);
+    prefix
+}
+
+/// Doubles each `@` of a text value, except in a leading escape sequence
+fn escape(value: &str) -> Cow<'_, str> {
+    if !value.contains('@') {
+        return Cow::Borrowed(value);
+    }
+    let escape_length = value
+        .strip_prefix("@#")
+        .and_then(|rest| rest.find('@'))
+        .map_or(0, |length| length + 3);
+    let (escape, text) = value.split_at(escape_length);
+    Cow::Owned(format!("{escape}{}", text.replace('@', "@@")))
+}
+
+/// Doubles an `@` starting a text value, the only `@` GEDCOM 7.0 escapes
+fn escape_leading(value: &str) -> Cow<'_, str> {
+    if value.starts_with('@') {
+        Cow::Owned(format!("@{value}"))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// The byte index to split a value at for its first part to fit in `room` characters. Splits
+/// next to a space, which some programs trim, and within an escaped `@@` are avoided.
+fn split_point(value: &str, room: usize) -> usize {
+    let boundaries: Vec<usize> = (value.char_indices().map(|(index, _)| index))
+        .chain(iter::once(value.len()))
+        .collect();
+    let room = room.max(1);
+    if boundaries.len() <= room + 1 {
+        return value.len();
+    }
+    let bytes = value.as_bytes();
+    let splits_escape = |index: usize| bytes[index - 1] == b'@' && bytes[index] == b'@';
+    let splits_space = |index: usize| bytes[index - 1] == b' ' || bytes[index] == b' ';
+
+    let candidates = || boundaries[1..=room].iter().rev().copied();
+    candidates()
+        .find(|&index| !splits_escape(index) && !splits_space(index))
+        .or_else(|| candidates().find(|&index| !splits_escape(index)))
+        .unwrap_or(boundaries[room])
+}
@@ -1,6 +1,18 @@
 #[cfg(test)]
 mod tests {
-    use gedcom::parser::Parser;
+    use gedcom::convert::convert;
+    use gedcom::gedzip::{Gedzip, GedzipWriter};
+    use gedcom::parser::{Parser, ParserOptions, Strictness};
+    use gedcom::tokenizer::{Token, Tokenizer};
+    use gedcom::types::{
+        AdoptedBy, Crop, EventType, Family, GedcomVersion, Gender, LdsOrdinanceType, Media,
+        MediaFile, MediaLink, NoteRef,
+    };
+    use gedcom::writer::{GedcomWriter, LineTerminator, WriterOptions};
+    use gedcom::{encoding, GedcomData, GedcomError, Position, Record, Severity, Span};
+    use std::borrow::Cow;
+    use std::fs::File;
+    use std::io::BufReader;
     use std::path::PathBuf;
 
     fn read_relative(path: &str) -> String {
@@ -10,12 +22,13 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::len_zero)]
     fn parses_basic_gedcom() {
         let simple_ged: String = read_relative("./tests/fixtures/simple.ged");
         assert!(simple_ged.len() > 0);
 
-        let mut parser = Parser::new(simple_ged.chars());
-        let data = parser.parse_record();
+        let mut parser = Parser::new(&simple_ged);
+        let data = parser.parse_record().unwrap();
         assert_eq!(data.individuals.len(), 3);
         assert_eq!(data.families.len(), 1);
         assert_eq!(data.submitters.len(), 1);
@@ -50,4 +63,1468 @@ mod tests {
         assert_eq!(events[0].event.to_string(), "Marriage");
         assert_eq!(events[0].date.as_ref().unwrap(), "1 APR 1950");
     }
+
+    #[test]
+    fn returns_error_on_unhandled_tag() {
+        let source = "0 HEAD\n1 CHAR ASCII\n0 @I1@ INDI\n1 NAME /Person/\n1 FOO bar\n0 TRLR";
+
+        let mut parser = Parser::new(source);
+        let err = parser.parse_record().unwrap_err();
+        assert_eq!(
+            err,
+            GedcomError::UnexpectedTag {
+                line: 5,
+                record: "Individual",
+                tag: "FOO".to_string(),
+            }
+        );
+        assert_eq!(err.line(), Some(5));
+    }
+
+    #[test]
+    fn lenient_parsing_skips_unhandled_tags() {
+        let source = "0 HEAD\n1 CHAR ASCII\n0 @I1@ INDI\n1 NAME /Person/\n1 FOO bar\n2 BAZ qux\n1 SEX X\n1 BIRT\n2 DATE 1 JAN 1900\n0 TRLR";
+
+        let options = ParserOptions {
+            strictness: Strictness::Lenient,
+            ..ParserOptions::default()
+        };
+        let mut parser = Parser::with_options(source, options);
+        let data = parser.parse_record().unwrap();
+        assert_eq!(data.individuals.len(), 1);
+        assert_eq!(data.individuals[0].events.len(), 1);
+
+        let diagnostics = parser.diagnostics();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].line, 5);
+        assert_eq!(diagnostics[0].level, 1);
+        assert_eq!(diagnostics[0].tag, "FOO");
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[1].tag, "SEX");
+        assert_eq!(diagnostics[1].severity, Severity::Error);
+
+        // a line with a level but no tag is skipped
+        let source = "0 HEAD\n0 @I1@ INDI\n1\n1 SEX M\n0 TRLR\n";
+        let err = Parser::new(source).parse_record().unwrap_err();
+        assert_eq!(err.line(), Some(3));
+
+        let mut parser = Parser::with_options(source, options);
+        let data = parser.parse_record().unwrap();
+        assert!(matches!(data.individuals[0].sex, Gender::Male));
+        let diagnostics = parser.diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, 3);
+        assert_eq!(diagnostics[0].level, 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn parses_header() {
+        let sample_ged: String = read_relative("./tests/fixtures/sample.ged");
+
+        let mut parser = Parser::new(&sample_ged);
+        let data = parser.parse_record().unwrap();
+        let header = data.header.unwrap();
+
+        let gedcom = header.gedcom.unwrap();
+        assert_eq!(gedcom.version.unwrap(), "5.5.5");
+        assert_eq!(gedcom.form.unwrap(), "LINEAGE-LINKED");
+        assert_eq!(header.encoding.unwrap().value.unwrap(), "UTF-8");
+
+        let source = header.source.unwrap();
+        assert_eq!(source.value.unwrap(), "GS");
+        assert_eq!(source.version.unwrap(), "5.5.5");
+        assert_eq!(source.name.unwrap(), "GEDCOM Specification");
+        let corporation = source.corporation.unwrap();
+        assert_eq!(corporation.value.unwrap(), "gedcom.org");
+        assert_eq!(corporation.website.unwrap(), "www.gedcom.org");
+
+        let date = header.date.unwrap();
+        assert_eq!(date.value.unwrap(), "2 Oct 2019");
+        assert_eq!(date.time.unwrap(), "0:00:00");
+        assert_eq!(header.submitter_tag.unwrap(), "@U1@");
+        assert_eq!(header.filename.unwrap(), "555Sample.ged");
+        assert_eq!(header.language.unwrap(), "English");
+    }
+
+    #[test]
+    fn parses_notes() {
+        let source = "0 HEAD\n1 CHAR ASCII\n\
+            0 @I1@ INDI\n1 NAME /Person/\n1 NOTE @N1@\n1 BIRT\n2 NOTE Born at home\n\
+            0 @N1@ NOTE First line\n1 CONT second line\n1 SOUR @S1@\n2 PAGE 12\n\
+            1 REFN 42\n2 TYPE index\n1 RIN 7\n1 CHAN\n2 DATE 1 JAN 2000\n3 TIME 12:00\n\
+            0 TRLR";
+
+        let mut parser = Parser::new(source);
+        let data = parser.parse_record().unwrap();
+
+        let individual = &data.individuals[0];
+        assert_eq!(individual.notes, vec![NoteRef::Pointer("@N1@".to_string())]);
+        assert_eq!(
+            individual.events[0].notes,
+            vec![NoteRef::Text("Born at home".to_string())]
+        );
+
+        assert_eq!(data.notes.len(), 1);
+        let note = &data.notes[0];
+        assert_eq!(note.xref.as_ref().unwrap(), "@N1@");
+        assert_eq!(note.value.as_ref().unwrap(), "First line\nsecond line");
+        assert_eq!(note.citations[0].page.as_ref().unwrap(), "12");
+        assert_eq!(
+            note.user_references[0].reference_type.as_ref().unwrap(),
+            "index"
+        );
+        assert_eq!(note.automated_record_id.as_ref().unwrap(), "7");
+        let change_date = note.change_date.as_ref().unwrap();
+        assert_eq!(change_date.date.as_ref().unwrap(), "1 JAN 2000");
+        assert_eq!(change_date.time.as_ref().unwrap(), "12:00");
+    }
+
+    #[test]
+    fn parses_multimedia() {
+        let source = "0 HEAD\n1 CHAR ASCII\n\
+            0 @I1@ INDI\n1 NAME /Person/\n1 OBJE @M1@\n\
+            1 BIRT\n2 OBJE\n3 FILE birth.jpg\n4 FORM jpeg\n5 MEDI photo\n3 TITL Birth certificate\n\
+            0 @M1@ OBJE\n1 FILE portrait.png\n2 FORM png\n3 TYPE photo\n2 TITL Portrait\n\
+            1 NOTE Taken in 1920\n1 RIN 3\n\
+            0 TRLR";
+
+        let mut parser = Parser::new(source);
+        let data = parser.parse_record().unwrap();
+
+        let individual = &data.individuals[0];
+        assert_eq!(
+            individual.multimedia,
+            vec![MediaLink::Pointer("@M1@".to_string())]
+        );
+        match &individual.events[0].multimedia[0] {
+            MediaLink::Inline { files, title } => {
+                assert_eq!(files[0].value.as_ref().unwrap(), "birth.jpg");
+                assert_eq!(files[0].form.as_ref().unwrap(), "jpeg");
+                assert_eq!(files[0].media_type.as_ref().unwrap(), "photo");
+                assert_eq!(title.as_ref().unwrap(), "Birth certificate");
+            }
+            link => panic!("expected inline multimedia, found {:?}", link),
+        }
+
+        assert_eq!(data.multimedia.len(), 1);
+        let media = &data.multimedia[0];
+        assert_eq!(media.xref.as_ref().unwrap(), "@M1@");
+        assert_eq!(media.files[0].value.as_ref().unwrap(), "portrait.png");
+        assert_eq!(media.files[0].media_type.as_ref().unwrap(), "photo");
+        assert_eq!(media.files[0].title.as_ref().unwrap(), "Portrait");
+        assert_eq!(
+            media.notes,
+            vec![NoteRef::Text("Taken in 1920".to_string())]
+        );
+        assert_eq!(media.automated_record_id.as_ref().unwrap(), "3");
+    }
+
+    #[test]
+    fn parses_submission() {
+        let source = "0 HEAD\n1 CHAR ASCII\n1 SUBM @U1@\n1 SUBN @SUB1@\n\
+            0 @U1@ SUBM\n1 NAME Submitter\n\
+            0 @SUB1@ SUBN\n1 SUBM @U1@\n1 FAMF Family file\n1 TEMP SLAKE\n\
+            1 ANCE 4\n1 DESC 2\n1 ORDI yes\n1 RIN 11\n\
+            0 TRLR";
+
+        let mut parser = Parser::new(source);
+        let data = parser.parse_record().unwrap();
+
+        let submission = data.header_submission().unwrap();
+        assert_eq!(submission.submitter.as_ref().unwrap(), "@U1@");
+        assert_eq!(submission.family_file.as_ref().unwrap(), "Family file");
+        assert_eq!(submission.temple_code.as_ref().unwrap(), "SLAKE");
+        assert_eq!(submission.ancestor_generations.as_ref().unwrap(), "4");
+        assert_eq!(submission.descendant_generations.as_ref().unwrap(), "2");
+        assert_eq!(submission.ordinance_process_flag.as_ref().unwrap(), "yes");
+        assert_eq!(submission.automated_record_id.as_ref().unwrap(), "11");
+    }
+
+    #[test]
+    fn keeps_user_defined_tags() {
+        let source = "0 HEAD\n1 CHAR ASCII\n\
+            0 @I1@ INDI\n1 NAME Jane /Doe/\n2 _MARNM Smith\n1 _UID 1234ABCD\n\
+            1 BIRT\n2 SOUR @S1@\n3 _APID 1,7602::2771226\n\
+            1 _MILT Served in the army\n2 DATE 1917\n2 PLAC France\n\
+            0 @X1@ _PLAC Paris\n1 MAP\n2 LATI N48.8566\n\
+            0 TRLR";
+
+        let mut parser = Parser::new(source);
+        let data = parser.parse_record().unwrap();
+
+        let individual = &data.individuals[0];
+        let name = individual.name.as_ref().unwrap();
+        assert_eq!(name.extensions[0].tag, "_MARNM");
+        assert_eq!(name.extensions[0].value.as_ref().unwrap(), "Smith");
+
+        assert_eq!(individual.extensions.len(), 2);
+        assert_eq!(individual.extensions[0].tag, "_UID");
+        assert_eq!(individual.extensions[0].value.as_ref().unwrap(), "1234ABCD");
+        let military = &individual.extensions[1];
+        assert_eq!(military.level, 1);
+        assert_eq!(military.children.len(), 2);
+        assert_eq!(
+            military.child("PLAC").unwrap().value.as_ref().unwrap(),
+            "France"
+        );
+
+        let citation = &individual.events[0].citations[0];
+        assert_eq!(citation.extensions[0].tag, "_APID");
+
+        assert_eq!(data.extensions.len(), 1);
+        let place = &data.extensions[0];
+        assert_eq!(place.xref.as_ref().unwrap(), "@X1@");
+        assert_eq!(place.tag, "_PLAC");
+        let latitude = place.child("MAP").unwrap().child("LATI").unwrap();
+        assert_eq!(latitude.level, 2);
+        assert_eq!(latitude.value.as_ref().unwrap(), "N48.8566");
+    }
+
+    #[test]
+    fn parses_untyped_tree() {
+        let source = "0 HEAD\n1 GEDC\n2 VERS 5.5.1\n\
+            0 @I1@ INDI\n1 NAME Jane /Doe/\n1 OCCU Farmer\n2 DATE 1850\n\
+            0 TRLR";
+
+        let mut parser = Parser::new(source);
+        let tree = parser.parse_tree().unwrap();
+        let tags: Vec<&str> = tree.iter().map(|node| node.tag.as_str()).collect();
+        assert_eq!(tags, ["HEAD", "INDI", "TRLR"]);
+
+        let individual = &tree[1];
+        assert_eq!(individual.xref.as_ref().unwrap(), "@I1@");
+        assert_eq!(individual.line, 4);
+        let occupation = individual.child("OCCU").unwrap();
+        assert_eq!(occupation.line, 6);
+        assert_eq!(occupation.child("DATE").unwrap().level, 2);
+
+        // typed records and events keep the node they were built from
+        let source = "0 HEAD\n1 GEDC\n2 VERS 5.5.1\n\
+            0 @I1@ INDI\n1 NAME Jane /Doe/\n1 BIRT\n2 DATE 1850\n2 _SEEN Yes\n\
+            0 TRLR";
+        let tree = Parser::new(source).parse_tree().unwrap();
+        let data = Parser::new(source).parse_record().unwrap();
+        let individual = &data.individuals[0];
+        assert_eq!(individual.node(), Some(&tree[1]));
+        assert_eq!(individual.span(), Some(tree[1].span));
+        assert_eq!(individual.events[0].node(), tree[1].child("BIRT"));
+        assert_eq!(data.header.unwrap().node(), Some(&tree[0]));
+    }
+
+    #[test]
+    fn parses_individual_events() {
+        let source = "0 HEAD\n1 CHAR ASCII\n\
+            0 @I1@ INDI\n1 NAME John /Doe/\n\
+            1 BIRT Y\n2 FAMC @F1@\n\
+            1 CHR\n2 DATE 3 MAR 1850\n\
+            1 ADOP\n2 FAMC @F2@\n3 ADOP WIFE\n\
+            1 IMMI\n2 PLAC New York\n\
+            1 EVEN Won the county fair\n2 TYPE Award\n2 DATE 1870\n\
+            1 CREM\n\
+            0 TRLR";
+
+        let mut parser = Parser::new(source);
+        let data = parser.parse_record().unwrap();
+
+        let events = &data.individuals[0].events;
+        let types: Vec<&EventType> = events.iter().map(|event| &event.event).collect();
+        assert_eq!(
+            types,
+            [
+                &EventType::Birth,
+                &EventType::Christening,
+                &EventType::Adoption,
+                &EventType::Immigration,
+                &EventType::Event,
+                &EventType::Cremation,
+            ]
+        );
+
+        assert_eq!(events[0].value.as_ref().unwrap(), "Y");
+        assert_eq!(events[0].family.as_ref().unwrap(), "@F1@");
+        assert_eq!(events[2].family.as_ref().unwrap(), "@F2@");
+        assert_eq!(events[2].adopted_by, Some(AdoptedBy::Wife));
+        assert_eq!(events[3].place.as_ref().unwrap(), "New York");
+        assert_eq!(events[4].value.as_ref().unwrap(), "Won the county fair");
+        assert_eq!(events[4].event_type.as_ref().unwrap(), "Award");
+    }
+
+    #[test]
+    fn parses_family_events() {
+        let source = "0 HEAD\n1 CHAR ASCII\n\
+            0 @F1@ FAM\n1 HUSB @I1@\n1 WIFE @I2@\n\
+            1 ENGA\n2 DATE 1880\n\
+            1 MARR\n2 HUSB\n3 AGE 25y\n2 WIFE\n3 AGE 22y\n\
+            1 DIV\n2 DATE 1890\n\
+            1 MARR\n2 DATE 1895\n\
+            1 CENS\n2 DATE 1900\n\
+            0 TRLR";
+
+        let mut parser = Parser::new(source);
+        let data = parser.parse_record().unwrap();
+
+        let events = data.families[0].get_events();
+        let types: Vec<&EventType> = events.iter().map(|event| &event.event).collect();
+        assert_eq!(
+            types,
+            [
+                &EventType::Engagement,
+                &EventType::Marriage,
+                &EventType::Divorce,
+                &EventType::Marriage,
+                &EventType::Census,
+            ]
+        );
+        assert_eq!(events[1].husband_age.as_ref().unwrap(), "25y");
+        assert_eq!(events[1].wife_age.as_ref().unwrap(), "22y");
+        assert_eq!(events[3].date.as_ref().unwrap(), "1895");
+    }
+
+    #[test]
+    fn tokenizes_borrowed_slices() {
+        let source = "0 HEAD\r\n1 CHAR UTF-8\r\n0 @I1@ INDI\r\n1 NAME Zoë /Ångström/\r\n0 TRLR\r\n";
+
+        let mut tokenizer = Tokenizer::new(source);
+        let mut tokens = Vec::new();
+        while !tokenizer.done() {
+            tokenizer.next_token().unwrap();
+            if let Token::Pointer(value) | Token::Tag(value) | Token::LineValue(value) =
+                tokenizer.current_token()
+            {
+                tokens.push(value);
+            }
+        }
+        assert_eq!(tokenizer.line, 5);
+        drop(tokenizer);
+        for value in &tokens {
+            // every token is a slice of the source, outliving the tokenizer
+            let Cow::Borrowed(value) = value else {
+                panic!("expected {:?} to borrow from the source", value);
+            };
+            let offset = value.as_ptr() as usize - source.as_ptr() as usize;
+            assert_eq!(&source[offset..offset + value.len()], *value);
+        }
+        assert_eq!(
+            tokens,
+            [
+                "HEAD",
+                "CHAR",
+                "UTF-8",
+                "@I1@",
+                "INDI",
+                "NAME",
+                "Zoë /Ångström/",
+                "TRLR"
+            ]
+        );
+
+        let mut tokenizer = Tokenizer::new("0 @I1@ INDI\n1 NOTE a@@b\n");
+        while !matches!(tokenizer.current_token(), Token::LineValue(_)) {
+            tokenizer.next_token().unwrap();
+        }
+        let value = tokenizer.current_token();
+        assert!(matches!(value, Token::LineValue(Cow::Owned(ref text)) if text == "a@b"));
+
+        let tree = Parser::new(source).parse_tree().unwrap();
+        assert_eq!(tree.len(), 3);
+        assert_eq!(
+            tree[1].child("NAME").unwrap().value.as_ref().unwrap(),
+            "Zoë /Ångström/"
+        );
+    }
+
+    #[test]
+    fn decodes_declared_character_sets() {
+        // ANSEL writes diacritics before the letter they modify
+        let ansel = b"0 HEAD\n1 CHAR ANSEL\n0 @I1@ INDI\n1 NAME Fran\xf0cois /Dvo\xe9r\xe2ak/\n\
+            2 NICK \xe8A\xa5 Nguy\xe3\xf2en\n0 TRLR";
+        assert_eq!(encoding::declared_charset(ansel), Some("ANSEL"));
+        let contents = encoding::decode(ansel).unwrap();
+        let data = Parser::new(&contents).parse_tree().unwrap();
+        let name = data[1].child("NAME").unwrap();
+        assert_eq!(name.value.as_ref().unwrap(), "François /Dvořák/");
+        assert_eq!(
+            name.child("NICK").unwrap().value.as_ref().unwrap(),
+            "\u{c4}\u{c6} Nguy\u{1ec7}n"
+        );
+
+        let ansi = b"0 HEAD\n1 CHAR ANSI\n0 @I1@ INDI\n1 NAME Ren\xe9 \x93Bud\x94\n0 TRLR";
+        assert!(encoding::decode(ansi).unwrap().contains("René “Bud”"));
+
+        let ibm = b"0 HEAD\n1 CHAR IBMPC\n0 @I1@ INDI\n1 NAME J\x81rgen\n0 TRLR";
+        assert!(encoding::decode(ibm).unwrap().contains("Jürgen"));
+
+        // the tag is found with any delimiters the parser accepts
+        let tabs = b"0 HEAD\n1\tCHAR  ANSI\n0 @I1@ INDI\n1 NAME J\xfcrgen\n0 TRLR";
+        assert_eq!(encoding::declared_charset(tabs), Some("ANSI"));
+        let data = Parser::from_reader(&tabs[..]).parse_record().unwrap();
+        let name = data.individuals[0].name.as_ref().unwrap();
+        assert_eq!(name.value.as_deref(), Some("Jürgen"));
+
+        // undecoded ASCII files are borrowed
+        let ascii = b"0 HEAD\n1 CHAR ASCII\n0 TRLR";
+        assert!(matches!(encoding::decode(ascii).unwrap(), Cow::Borrowed(_)));
+
+        let invalid = b"0 HEAD\n1 CHAR UTF-8\n0 @I1@ INDI\n1 NAME Ren\xe9\n0 TRLR";
+        let err = encoding::decode(invalid).unwrap_err();
+        assert_eq!(err.line(), Some(4));
+    }
+
+    #[test]
+    fn parses_bytes_with_detected_encoding() {
+        let source = "0 HEAD\r\n1 CHAR UNICODE\r\n0 @I1@ INDI\r\n1 NAME Zoë /Ångström/\r\n0 TRLR";
+
+        let mut little_endian = vec![0xFF, 0xFE];
+        let mut big_endian = Vec::new();
+        for unit in source.encode_utf16() {
+            little_endian.extend(unit.to_le_bytes());
+            big_endian.extend(unit.to_be_bytes());
+        }
+        for bytes in [&little_endian[..], &big_endian[..]] {
+            let data = Parser::from_bytes(bytes).unwrap().parse_record().unwrap();
+            let name = data.individuals[0].name.as_ref().unwrap();
+            assert_eq!(name.value.as_ref().unwrap(), "Zoë /Ångström/");
+        }
+
+        // an odd final byte is not half of a character
+        let mut odd = little_endian[..little_endian.len() - 12].to_vec();
+        odd.push(b'\n');
+        let err = Parser::from_bytes(&odd).err().unwrap();
+        let expected = GedcomError::InvalidEncoding {
+            line: 4,
+            encoding: "UTF-16".to_string(),
+        };
+        assert_eq!(err, expected);
+        let err = Parser::from_reader(&odd[..]).parse_tree().unwrap_err();
+        assert_eq!(err, expected);
+
+        // an unpaired surrogate on the third line of a file ending its lines with `\r`
+        let mut lone_surrogate = vec![0xFF, 0xFE];
+        for unit in "0 HEAD\r1 CHAR UNICODE\r\n\r0 @I1@ INDI\r1 NAME ".encode_utf16() {
+            lone_surrogate.extend(unit.to_le_bytes());
+        }
+        lone_surrogate.extend([0x00, 0xD8]);
+        let err = Parser::from_bytes(&lone_surrogate).err().unwrap();
+        assert_eq!(err.line(), Some(5));
+        let err = Parser::from_reader(&lone_surrogate[..])
+            .parse_tree()
+            .unwrap_err();
+        assert_eq!(err.line(), Some(5));
+
+        let utf8 = b"\xef\xbb\xbf0 HEAD\n1 CHAR UTF-8\n0 @I1@ INDI\n0 TRLR";
+        let data = Parser::from_bytes(utf8).unwrap().parse_record().unwrap();
+        assert_eq!(data.individuals.len(), 1);
+
+        let mismatched = b"\xef\xbb\xbf0 HEAD\n1 CHAR ANSEL\n0 TRLR";
+        let Err(err) = Parser::from_bytes(mismatched) else {
+            panic!("expected the CHAR to contradict the byte order mark");
+        };
+        assert_eq!(
+            err,
+            GedcomError::EncodingMismatch {
+                line: 2,
+                declared: "ANSEL".to_string(),
+                detected: "UTF-8",
+            }
+        );
+    }
+
+    #[test]
+    fn parses_from_reader() {
+        let sample_ged = read_relative("./tests/fixtures/sample.ged");
+        let file = File::open("./tests/fixtures/sample.ged").unwrap();
+        let streamed = Parser::from_reader(BufReader::new(file))
+            .parse_tree()
+            .unwrap();
+        assert_eq!(streamed, Parser::new(&sample_ged).parse_tree().unwrap());
+
+        let ansel = b"0 HEAD\r\n1 CHAR ANSEL\r\n0 @I1@ INDI\r\n1 NAME Fran\xf0cois /Dvo\xe9r\xe2ak/\r\n0 TRLR\r\n";
+        let data = Parser::from_reader(&ansel[..]).parse_record().unwrap();
+        let name = data.individuals[0].name.as_ref().unwrap();
+        assert_eq!(name.value.as_ref().unwrap(), "François /Dvořák/");
+
+        let mut utf16 = vec![0xFE, 0xFF];
+        for unit in "0 HEAD\n1 CHAR UNICODE\n0 @I1@ INDI\n1 NAME Zoë\n0 TRLR".encode_utf16() {
+            utf16.extend(unit.to_be_bytes());
+        }
+        let data = Parser::from_reader(&utf16[..]).parse_record().unwrap();
+        let name = data.individuals[0].name.as_ref().unwrap();
+        assert_eq!(name.value.as_ref().unwrap(), "Zoë");
+
+        let invalid = b"0 HEAD\n1 CHAR UTF-8\n0 @I1@ INDI\n1 NAME Ren\xe9\n0 TRLR";
+        let err = Parser::from_reader(&invalid[..])
+            .parse_record()
+            .unwrap_err();
+        assert_eq!(err.line(), Some(4));
+
+        // both read files without a CHAR, or with an unknown one, as UTF-8
+        for (undeclared, line) in [
+            (&b"0 HEAD\n0 @I1@ INDI\n1 NAME Ren\xe9\n0 TRLR"[..], 3),
+            (
+                b"0 HEAD\n1 CHAR EBCDIC\n0 @I1@ INDI\n1 NAME Ren\xe9\n0 TRLR",
+                4,
+            ),
+        ] {
+            let expected = Err(GedcomError::InvalidEncoding {
+                line,
+                encoding: "UTF-8".to_string(),
+            });
+            let from_bytes =
+                Parser::from_bytes(undeclared).and_then(|mut parser| parser.parse_tree());
+            assert_eq!(from_bytes, expected);
+            assert_eq!(Parser::from_reader(undeclared).parse_tree(), expected);
+        }
+
+        // lines of the header before its CHAR are decoded in the character set it names
+        let ansel = b"0 HEAD\n1 SOUR Dvo\xe9r\xe2ak\n1 CHAR ANSEL\n0 TRLR";
+        let from_bytes = Parser::from_bytes(ansel).unwrap().parse_tree().unwrap();
+        let streamed = Parser::from_reader(&ansel[..]).parse_tree().unwrap();
+        assert_eq!(streamed, from_bytes);
+        let source = streamed[0].child("SOUR").unwrap();
+        assert_eq!(source.value.as_deref(), Some("Dvořák"));
+    }
+
+    #[test]
+    fn tracks_spans() {
+        let sample = "0 HEAD\n0 @I1@ INDI\n1 NAME Zoë\n1 BIRT\n2 DATE 1900\n0 TRLR\n";
+
+        // columns of the first line start after a byte order mark, offsets include it
+        let mut tokenizer = Tokenizer::new(format!("\u{feff}{sample}"));
+        tokenizer.next_token().unwrap();
+        tokenizer.next_token().unwrap();
+        assert_eq!(tokenizer.current_token(), Token::Tag("HEAD".into()));
+        let start = tokenizer.current_span().start;
+        assert_eq!((start.line, start.column, start.offset), (1, 3, 5));
+
+        let mut tokenizer = Tokenizer::new(sample);
+        while tokenizer.current_token() != Token::LineValue("Zoë".into()) {
+            tokenizer.next_token().unwrap();
+        }
+        let span = tokenizer.current_span();
+        assert_eq!(
+            span.start,
+            Position {
+                line: 3,
+                column: 8,
+                offset: 26
+            }
+        );
+        assert_eq!(
+            span.end,
+            Position {
+                line: 3,
+                column: 11,
+                offset: 30
+            }
+        );
+
+        let expected = Span {
+            start: Position {
+                line: 2,
+                column: 1,
+                offset: 7,
+            },
+            end: Position {
+                line: 5,
+                column: 12,
+                offset: 49,
+            },
+        };
+        let data = Parser::new(sample).parse_record().unwrap();
+        let individual = &data.individuals[0];
+        assert_eq!(individual.span(), Some(expected));
+        assert_eq!(individual.events[0].span.unwrap().start.offset, 31);
+        assert_eq!(individual.events[0].span.unwrap().end, expected.end);
+
+        let streamed = Parser::from_reader(sample.as_bytes())
+            .parse_record()
+            .unwrap();
+        assert_eq!(streamed.individuals[0].span(), Some(expected));
+    }
+
+    #[test]
+    fn iterates_over_records() {
+        let sample_ged = read_relative("./tests/fixtures/sample.ged");
+        let file = File::open("./tests/fixtures/sample.ged").unwrap();
+        let mut parser = Parser::from_reader(BufReader::new(file));
+
+        let mut individuals = Vec::new();
+        let mut families = 0;
+        let mut last = None;
+        for record in parser.records() {
+            match record.unwrap() {
+                Record::Individual(individual) => individuals.push(individual.xref.unwrap()),
+                Record::Family(_) => families += 1,
+                record => last = Some(record),
+            }
+        }
+        assert_eq!(individuals, ["@I1@", "@I2@", "@I3@"]);
+        assert_eq!(families, 2);
+        assert!(matches!(last, Some(Record::Trailer)));
+
+        let data = Parser::new(&sample_ged).parse_record().unwrap();
+        assert_eq!(data.individuals.len(), individuals.len());
+
+        let mut parser = Parser::new("0 HEAD\n0 @I1@ INDI\n1 FOO bar\n0 TRLR");
+        let mut records = parser.records();
+        assert!(matches!(records.next(), Some(Ok(Record::Header(_)))));
+        assert!(matches!(records.next(), Some(Err(_))));
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn parses_records_in_parallel() {
+        let sample_ged = read_relative("./tests/fixtures/sample.ged");
+        let mut contents = String::from("0 HEAD\n1 CHAR UTF-8\n");
+        for index in 0..500 {
+            contents += &format!("0 @I{index}@ INDI\n1 NAME Person {index}\n1 FOO bar\n");
+            contents += &format!("0 @F{index}@ FAM\n1 HUSB @I{index}@\n1 MARR\n2 DATE {index}\n");
+        }
+        contents += "0 TRLR\n0 @I9@ INDI\n";
+
+        for source in [sample_ged.as_str(), &contents] {
+            let lenient = ParserOptions {
+                strictness: Strictness::Lenient,
+                threads: 1,
+                ..ParserOptions::default()
+            };
+            let mut sequential = Parser::with_options(source, lenient);
+            let expected = sequential.parse_record().unwrap();
+
+            let options = ParserOptions {
+                threads: 4,
+                ..lenient
+            };
+            let mut parallel = Parser::with_options(source, options);
+            let data = parallel.parse_record().unwrap();
+            assert_eq!(format!("{:?}", data), format!("{:?}", expected));
+            assert_eq!(parallel.diagnostics(), sequential.diagnostics());
+        }
+
+        let options = ParserOptions {
+            threads: 4,
+            ..ParserOptions::default()
+        };
+        let err = Parser::with_options(&contents, options)
+            .parse_record()
+            .unwrap_err();
+        assert_eq!(err.line(), Some(5));
+    }
+
+    #[test]
+    fn joins_continuation_lines() {
+        let source = "0 HEAD\n0 @I1@ INDI\n1 NAME Maria Jo\n2 CONC sefa /Garc\n2 CONC ía/\n2 GIVN Maria\n\
+            1 BIRT\n2 PLAC San Juan,\n3 CONC  Puerto Rico\n2 SOUR @S1@\n3 PAGE p. 1\n4 CONC 2\n\
+            0 @S1@ SOUR\n1 TITL Parish\n2 CONC  records\n2 CONT of San Juan\n1 DATA\n2 AGNC Dio\n3 CONC cese\n\
+            0 @N1@ NOTE \n1 CONC A note\n1 CONT \n1 CONT on three lines\n0 TRLR";
+
+        let data = Parser::new(source).parse_record().unwrap();
+        let individual = &data.individuals[0];
+        let name = individual.name.as_ref().unwrap();
+        assert_eq!(name.value.as_deref(), Some("Maria Josefa /García/"));
+        assert_eq!(name.given.as_deref(), Some("Maria"));
+        let birth = &individual.events[0];
+        assert_eq!(birth.place.as_deref(), Some("San Juan, Puerto Rico"));
+        assert_eq!(birth.citations[0].page.as_deref(), Some("p. 12"));
+        let source_record = &data.sources[0];
+        assert_eq!(
+            source_record.title.as_deref(),
+            Some("Parish records\nof San Juan")
+        );
+        assert_eq!(source_record.data.agency.as_deref(), Some("Diocese"));
+        assert_eq!(
+            data.notes[0].value.as_deref(),
+            Some("A note\n\non three lines")
+        );
+
+        // the tree holds the joined text unless the line splits are kept
+        let tree = Parser::new(source).parse_tree().unwrap();
+        let name_node = tree[1].child("NAME").unwrap();
+        assert_eq!(name_node.value.as_deref(), Some("Maria Josefa /García/"));
+        assert_eq!(name_node.children.len(), 1);
+
+        let options = ParserOptions {
+            keep_line_splits: true,
+            ..ParserOptions::default()
+        };
+        let tree = Parser::with_options(source, options).parse_tree().unwrap();
+        let name_node = tree[1].child("NAME").unwrap();
+        assert_eq!(name_node.value.as_deref(), Some("Maria Jo"));
+        assert_eq!(name_node.children.len(), 3);
+        assert_eq!(name_node.text().as_deref(), Some("Maria Josefa /García/"));
+        let data = Parser::with_options(source, options)
+            .parse_record()
+            .unwrap();
+        let name = data.individuals[0].name.as_ref().unwrap();
+        assert_eq!(name.value.as_deref(), Some("Maria Josefa /García/"));
+        assert_eq!(data.sources[0].data.agency.as_deref(), Some("Diocese"));
+    }
+
+    #[test]
+    fn handles_escapes_and_pointers() {
+        let mut tokenizer = Tokenizer::new("2 DATE @#DJULIAN@ 1 JAN 1700\n1 EMAIL me@@example.com");
+        let mut tokens = Vec::new();
+        while !tokenizer.done() {
+            tokenizer.next_token().unwrap();
+            tokens.push(format!("{:?}", tokenizer.current_token()));
+        }
+        assert_eq!(
+            tokens,
+            [
+                "Level(2)",
+                "Tag(\"DATE\")",
+                "Escape(\"@#DJULIAN@\")",
+                "LineValue(\"1 JAN 1700\")",
+                "Level(1)",
+                "Tag(\"EMAIL\")",
+                "LineValue(\"me@example.com\")",
+                "EOF",
+            ]
+        );
+
+        let source =
+            "0 HEAD\n0 @I1@ INDI\n1 NAME John @@home /Doe/\n1 BIRT\n2 DATE @#DJULIAN@ 1 JAN 1700\n\
+            1 FAMC @F1\n0 @F1@ FAM\n1 CHIL @I1@\n1 HUSB @ABCDEFGHIJKLMNOPQRST@\n\
+            1 WIFE @ABCDEFGHIJKLMNOPQRSTU@\n0 @_bad@ SOUR\n0 TRLR";
+        let mut parser = Parser::new(source);
+        let data = parser.parse_record().unwrap();
+        let individual = &data.individuals[0];
+        let name = individual.name.as_ref().unwrap();
+        assert_eq!(name.value.as_deref(), Some("John @home /Doe/"));
+        let birth = &individual.events[0];
+        assert_eq!(birth.date.as_deref(), Some("@#DJULIAN@ 1 JAN 1700"));
+        assert_eq!(data.families[0].children, ["@I1@"]);
+
+        let malformed: Vec<(u32, Severity, String)> = parser
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.line,
+                    diagnostic.severity,
+                    diagnostic.message.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            malformed,
+            [
+                (6, Severity::Warning, "Malformed pointer: @F1".to_string()),
+                // 21 characters between the `@` signs, one more than the 20 allowed
+                (
+                    10,
+                    Severity::Warning,
+                    "Malformed pointer: @ABCDEFGHIJKLMNOPQRSTU@".to_string()
+                ),
+                (
+                    11,
+                    Severity::Warning,
+                    "Malformed pointer: @_bad@".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn handles_line_terminators_and_whitespace() {
+        let lines = [
+            "0 HEAD",
+            "1 CHAR UTF-8",
+            "0 @I1@ INDI",
+            "",
+            "  1 NAME Jo\u{e3}o /Silva/",
+            "\t2\tGIVN\tJo\u{e3}o",
+            "1 BIRT",
+            "0 TRLR",
+        ];
+        for terminator in ["\n", "\r\n", "\r", "\n\r"] {
+            let source = lines.join(terminator) + terminator;
+            let tree = Parser::new(&source).parse_tree().unwrap();
+            let individual = &tree[1];
+            assert_eq!(individual.children.len(), 2);
+            let name = individual.child("NAME").unwrap();
+            assert_eq!(name.value.as_deref(), Some("João /Silva/"));
+            assert_eq!(name.line, 5);
+            assert_eq!(name.span.start.column, 3);
+            assert_eq!(name.child("GIVN").unwrap().value.as_deref(), Some("João"));
+            assert_eq!(tree[2].line, 8);
+
+            let streamed = Parser::from_reader(source.as_bytes()).parse_tree().unwrap();
+            assert_eq!(streamed.len(), 3);
+            assert_eq!(streamed[1], tree[1]);
+        }
+
+        let source =
+            "0 HEAD\n0 @I1@ INDI\nX NAME Skipped\n1 BIRT\n3 DATE 1900\n100 NOTE deep\n0 TRLR";
+        let err = Parser::new(source).parse_record().unwrap_err();
+        assert_eq!(
+            err,
+            GedcomError::InvalidLevel {
+                line: 3,
+                value: "X".to_string()
+            }
+        );
+
+        let options = ParserOptions {
+            strictness: Strictness::Lenient,
+            ..ParserOptions::default()
+        };
+        let mut parser = Parser::with_options(source, options);
+        let data = parser.parse_record().unwrap();
+        let birth = &data.individuals[0].events[0];
+        assert_eq!(birth.date.as_deref(), Some("1900"));
+        let reported: Vec<(u32, String)> = parser
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| (diagnostic.line, diagnostic.message.clone()))
+            .collect();
+        assert_eq!(
+            reported,
+            [
+                (3, "Invalid level: \"X\"".to_string()),
+                (5, "Invalid level: \"3\"".to_string()),
+                (6, "Invalid level: \"100\"".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn writes_gedcom() {
+        let sample = read_relative("./tests/fixtures/sample.ged");
+        let data = Parser::new(&sample).parse_record().unwrap();
+        let mut writer = GedcomWriter::new();
+        writer.write_data(&data);
+        let written = writer.as_str().to_string();
+        assert!(written.starts_with("0 HEAD\n1 SOUR GS\n"));
+        assert!(written.contains("1 GEDC\n2 VERS 5.5.1\n2 FORM LINEAGE-LINKED\n1 CHAR UTF-8\n"));
+        assert!(written.ends_with("0 TRLR\n"));
+
+        // writing the data read back gives the same text
+        let reread = Parser::new(&written).parse_record().unwrap();
+        assert_eq!(reread.individuals.len(), 3);
+        assert_eq!(reread.families[0].children, ["@I3@"]);
+        assert_eq!(reread.sources[0].repo_citations().len(), 1);
+        let mut writer = GedcomWriter::new();
+        writer.write_data(&reread);
+        assert_eq!(writer.as_str(), written);
+
+        // the number of children of a family is read back
+        let mut data = GedcomData::default();
+        let mut family = Family::new(Some("@F1@".to_string()));
+        family.num_children = Some(3);
+        data.add_family(family);
+        let mut writer = GedcomWriter::new();
+        writer.write_data(&data);
+        assert!(writer.as_str().contains("\n1 NCHI 3\n"));
+        let reread = Parser::new(writer.as_str()).parse_record().unwrap();
+        assert_eq!(reread.families[0].num_children, Some(3));
+
+        let note = format!("{}\nmail me@example.com", "A long note ".repeat(60));
+        let source = format!(
+            "0 HEAD\n0 @I1@ INDI\n1 NAME Jo\u{e3}o /Silva/\n1 BIRT\n2 DATE @#DJULIAN@ 1 JAN 1700\n\
+            1 NOTE {}\n0 TRLR\n",
+            note.replace('@', "@@").replace('\n', "\n2 CONT ")
+        );
+        let data = Parser::new(&source).parse_record().unwrap();
+        let options = WriterOptions {
+            terminator: LineTerminator::CrLf,
+            encoding: encoding::Charset::Ansel.into(),
+            ..WriterOptions::default()
+        };
+        let mut writer = GedcomWriter::with_options(options);
+        writer.write_data(&data);
+        let written = writer.as_str();
+        assert!(written.contains("\r\n1 CHAR ANSEL\r\n"));
+        assert!(written.contains("\r\n2 DATE @#DJULIAN@ 1 JAN 1700\r\n"));
+        assert!(written.contains("\r\n2 CONT mail me@@example.com\r\n"));
+        assert_eq!(written.matches(" CONC ").count(), 2);
+        assert!(written
+            .split_inclusive("\r\n")
+            .all(|line| line.chars().count() <= 255));
+
+        let bytes = writer.finish().unwrap();
+        assert!(bytes.windows(2).any(|pair| pair == [0xE4, b'a']));
+        let reread = Parser::from_bytes(&bytes).unwrap().parse_record().unwrap();
+        let individual = &reread.individuals[0];
+        let name = individual.name.as_ref().unwrap();
+        assert_eq!(name.value.as_deref(), Some("Jo\u{e3}o /Silva/"));
+        assert_eq!(individual.notes, [NoteRef::Text(note)]);
+
+        let options = WriterOptions {
+            encoding: encoding::Charset::Unicode.into(),
+            ..WriterOptions::default()
+        };
+        let mut writer = GedcomWriter::with_options(options);
+        writer.write_data(&data);
+        assert!(writer.finish().unwrap().starts_with(&[0xFF, 0xFE, b'0', 0]));
+
+        let options = WriterOptions {
+            encoding: encoding::Charset::Ascii.into(),
+            ..WriterOptions::default()
+        };
+        let mut writer = GedcomWriter::with_options(options);
+        writer.write_data(&data);
+        assert_eq!(
+            writer.finish().unwrap_err(),
+            GedcomError::InvalidEncoding {
+                line: 9,
+                encoding: "ASCII".to_string()
+            }
+        );
+
+        // text reading like a pointer is escaped, unlike pointers
+        let source =
+            "0 HEAD\n0 @I1@ INDI\n1 NOTE @@I1@@\n1 NOTE @N1@\n1 _NICK @@I1@@\n1 _LINK @I1@\n\
+            0 @N1@ NOTE text\n0 TRLR\n";
+        let data = Parser::new(source).parse_record().unwrap();
+        let notes = [
+            NoteRef::Text("@I1@".to_string()),
+            NoteRef::Pointer("@N1@".to_string()),
+        ];
+        assert_eq!(data.individuals[0].notes, notes);
+        for (version, escaped) in [(GedcomVersion::V5, "@@I1@@"), (GedcomVersion::V7, "@@I1@")] {
+            let options = WriterOptions {
+                version,
+                ..WriterOptions::default()
+            };
+            let mut writer = GedcomWriter::with_options(options);
+            writer.write_data(&data);
+            let written = writer.as_str();
+            assert!(written.contains(&format!("\n1 NOTE {escaped}\n")));
+            assert!(written.contains(&format!("\n1 _NICK {escaped}\n")));
+            assert!(written.contains("\n1 _LINK @I1@\n"));
+
+            let reread = Parser::new(written).parse_record().unwrap();
+            let individual = &reread.individuals[0];
+            assert_eq!(individual.notes, notes);
+            assert_eq!(individual.extensions[0].value.as_deref(), Some("@I1@"));
+            assert!(!individual.extensions[0].pointer);
+            assert!(individual.extensions[1].pointer);
+        }
+    }
+
+    #[test]
+    fn writes_trees_losslessly() {
+        let options = ParserOptions {
+            lossless: true,
+            ..ParserOptions::default()
+        };
+        for entry in std::fs::read_dir("./tests/fixtures").unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().and_then(|extension| extension.to_str()) != Some("ged") {
+                continue;
+            }
+            let bytes = std::fs::read(path).unwrap();
+
+            let mut parser = Parser::from_bytes(&bytes).unwrap();
+            parser.set_options(options);
+            let tree = parser.parse_tree().unwrap();
+            let writer_options = WriterOptions {
+                encoding: parser.encoding(),
+                ..WriterOptions::default()
+            };
+            assert_eq!(parser.encoding(), encoding::detect(&bytes));
+            let mut writer = GedcomWriter::with_options(writer_options);
+            writer.write_tree(&tree);
+            assert_eq!(writer.finish().unwrap(), bytes);
+
+            let mut parser = Parser::from_reader(bytes.as_slice());
+            parser.set_options(options);
+            let tree = parser.parse_tree().unwrap();
+            assert_eq!(parser.encoding(), writer_options.encoding);
+            let mut writer = GedcomWriter::with_options(writer_options);
+            writer.write_tree(&tree);
+            assert_eq!(writer.finish().unwrap(), bytes);
+        }
+
+        let source = "\u{feff}0 HEAD\r\n1 CHAR UTF-8\r\n\r\n0 @I1@ INDI\n  1 NAME John  me@home /Doe/ \n\
+            2 CONC tail\r1 _CUSTOM @X1@\n\r0 @X1@ _UNKNOWN value\n1 NOTE a @@ b\n0 FOO unknown\n0 TRLR\n\n";
+        let tree = Parser::with_options(source, options).parse_tree().unwrap();
+        assert_eq!(tree.len(), 5);
+        let name = tree[1].child("NAME").unwrap();
+        assert_eq!(name.raw.as_deref(), Some("1 NAME John  me@home /Doe/ \n"));
+        assert_eq!(name.text().as_deref(), Some("John  me@home /Doe/ tail"));
+        let mut writer = GedcomWriter::new();
+        writer.write_tree(&tree);
+        assert_eq!(writer.as_str(), source);
+
+        let mut parser = Parser::from_reader(source.as_bytes());
+        parser.set_options(options);
+        let streamed = parser.parse_tree().unwrap();
+        let mut writer = GedcomWriter::with_options(WriterOptions {
+            encoding: parser.encoding(),
+            ..WriterOptions::default()
+        });
+        writer.write_tree(&streamed);
+        assert_eq!(writer.as_str(), source.trim_start_matches('\u{feff}'));
+        assert_eq!(writer.finish().unwrap(), source.as_bytes());
+
+        // the typed records are built from the same lines
+        let data = Parser::with_options(source, options)
+            .parse_record()
+            .unwrap();
+        let name = data.individuals[0].name.as_ref().unwrap();
+        assert_eq!(name.value.as_deref(), Some("John  me@home /Doe/ tail"));
+
+        // lines without their text are written from the nodes
+        let mut tree = tree;
+        tree[2].raw = None;
+        tree[2].children[0].value = Some("a @ b".to_string());
+        tree[2].children[0].raw = None;
+        let mut writer = GedcomWriter::new();
+        writer.write_tree(&tree[2..3]);
+        assert_eq!(writer.as_str(), "0 @X1@ _UNKNOWN value\n1 NOTE a @@ b\n");
+    }
+
+    #[test]
+    fn parses_gedcom7() {
+        let source = read_relative("./tests/fixtures/gedcom7.ged");
+        let mut parser = Parser::new(&source);
+        let data = parser.parse_record().unwrap();
+        assert_eq!(parser.version(), GedcomVersion::V7);
+
+        let header = data.header.as_ref().unwrap();
+        assert_eq!(header.version(), GedcomVersion::V7);
+        assert_eq!(header.schema[0].tag, "_SKYPEID");
+        assert_eq!(header.schema[0].uri, "http://xmlns.com/foaf/0.1/skypeID");
+
+        let individual = &data.individuals[0];
+        assert!(matches!(individual.sex, Gender::Nonbinary));
+        let birth = &individual.events[0];
+        assert_eq!(birth.date_phrase.as_deref(), Some("Some time in 1900"));
+        assert_eq!(birth.sort_date.as_deref(), Some("1 JUN 1900"));
+        assert_eq!(birth.notes, [NoteRef::Pointer("@N1@".to_string())]);
+        let no_marriage = &individual.non_events[0];
+        assert_eq!(no_marriage.event, EventType::Marriage);
+        assert_eq!(no_marriage.date.as_deref(), Some("TO 1950"));
+        let initiatory = &individual.lds_ordinances[0];
+        assert_eq!(initiatory.ordinance, LdsOrdinanceType::Initiatory);
+        assert_eq!(initiatory.status.as_deref(), Some("COMPLETED"));
+        assert_eq!(initiatory.status_date.as_deref(), Some("27 MAR 2022"));
+        // only a leading @ is escaped and notes are never pointers
+        assert_eq!(
+            individual.notes,
+            [NoteRef::Text("@me on social media".to_string())]
+        );
+        assert_eq!(
+            individual.multimedia,
+            [MediaLink::Reference {
+                xref: "@O1@".to_string(),
+                crop: Some(Crop {
+                    top: Some(0),
+                    left: Some(0),
+                    height: Some(100),
+                    width: Some(100),
+                }),
+                title: Some("Cropped portrait".to_string()),
+            }]
+        );
+        assert_eq!(individual.external_ids[0].value.as_deref(), Some("123"));
+        assert_eq!(
+            individual.external_ids[0].id_type.as_deref(),
+            Some("https://example.com/ids")
+        );
+        assert_eq!(
+            individual.unique_ids,
+            ["bbcc0025-34cb-4542-8cfb-45ba201c9c2c"]
+        );
+        let sealing = &data.families[0].lds_ordinances[0];
+        assert_eq!(sealing.ordinance, LdsOrdinanceType::SpouseSealing);
+
+        let file = &data.multimedia[0].files[0];
+        assert_eq!(
+            file.translations[0].value.as_deref(),
+            Some("media/portrait.png")
+        );
+        assert_eq!(file.translations[0].mime.as_deref(), Some("image/png"));
+
+        let note = &data.notes[0];
+        assert_eq!(note.xref.as_deref(), Some("@N1@"));
+        assert_eq!(
+            note.value.as_deref(),
+            Some("Born at home, email a@@b.example")
+        );
+        assert_eq!(note.mime.as_deref(), Some("text/plain"));
+        assert_eq!(note.translations[0].language.as_deref(), Some("fr"));
+
+        let options = ParserOptions {
+            threads: 3,
+            ..ParserOptions::default()
+        };
+        let data = Parser::with_options(&source, options)
+            .parse_record()
+            .unwrap();
+        assert_eq!(
+            data.notes[0].value.as_deref(),
+            Some("Born at home, email a@@b.example")
+        );
+
+        // CONC is no longer allowed and 7.0 files are always UTF-8
+        let source = "0 HEAD\n1 GEDC\n2 VERS 7.0\n1 CHAR ANSEL\n0 @N1@ SNOTE a\n1 CONC b\n0 TRLR";
+        let err = Parser::new(source).parse_record().unwrap_err();
+        assert_eq!(
+            err,
+            GedcomError::InvalidValue {
+                line: 4,
+                record: "Header",
+                tag: "CHAR".to_string(),
+                value: "ANSEL".to_string()
+            }
+        );
+        let lenient = ParserOptions {
+            strictness: Strictness::Lenient,
+            ..ParserOptions::default()
+        };
+        let mut parser = Parser::with_options(source, lenient);
+        parser.parse_record().unwrap();
+        let lines: Vec<(u32, &str)> = parser
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| (diagnostic.line, diagnostic.tag.as_str()))
+            .collect();
+        assert_eq!(lines, [(4, "CHAR"), (6, "CONC")]);
+    }
+
+    #[test]
+    fn converts_between_versions() {
+        let source = "0 HEAD\n1 FILE tree.ged\n1 SUBN @S1@\n1 GEDC\n2 VERS 5.5.1\n0 @S1@ SUBN\n\
+            0 @I1@ INDI\n1 NAME John /Doe/\n1 BIRT\n2 DATE INT @#DJULIAN@ 1700 (about new year)\n\
+            1 DEAT\n2 DATE 30 JAN 1648/49\n1 RFN 1234:5\n1 _UID 0F0E0D0C\n1 NOTE @N1@\n\
+            1 OBJE\n2 FILE C:\\photos\\my photo.jpg\n3 FORM jpeg\n2 TITL Portrait\n\
+            0 @N1@ NOTE Born at home\n1 RIN 42\n0 TRLR\n";
+        let mut data = Parser::new(source).parse_record().unwrap();
+
+        let report = convert(&mut data, GedcomVersion::V7);
+        let tags: Vec<&str> = report.iter().map(|mapping| mapping.tag.as_str()).collect();
+        assert_eq!(tags, ["FILE", "SUBN"]);
+        let individual = &data.individuals[0];
+        assert_eq!(individual.unique_ids, ["0F0E0D0C"]);
+        assert!(individual.extensions.is_empty());
+        assert_eq!(individual.external_ids[0].value.as_deref(), Some("1234:5"));
+        let birth = &individual.events[0];
+        assert_eq!(birth.date.as_deref(), Some("JULIAN 1700"));
+        assert_eq!(birth.date_phrase.as_deref(), Some("about new year"));
+        let death = &individual.events[1];
+        assert_eq!(death.date.as_deref(), Some("30 JAN 1649"));
+        assert_eq!(death.date_phrase.as_deref(), Some("30 JAN 1648/49"));
+        let media = &data.multimedia[0];
+        assert_eq!(
+            individual.multimedia[0],
+            MediaLink::Pointer("@M1@".to_string())
+        );
+        let file = &media.files[0];
+        assert_eq!(file.title.as_deref(), Some("Portrait"));
+        assert_eq!(
+            file.value.as_deref(),
+            Some("file:///C:/photos/my%20photo.jpg")
+        );
+        assert_eq!(file.form.as_deref(), Some("image/jpeg"));
+
+        let mut writer = GedcomWriter::with_options(WriterOptions {
+            version: GedcomVersion::V7,
+            ..WriterOptions::default()
+        });
+        writer.write_data(&data);
+        let written = writer.as_str();
+        assert!(written.contains("1 GEDC\n2 VERS 7.0\n"));
+        assert!(!written.contains("CHAR") && !written.contains("SUBN"));
+        assert!(written.contains("1 SNOTE @N1@\n") && written.contains("0 @N1@ SNOTE Born"));
+        assert!(written.contains("2 DATE JULIAN 1700\n3 PHRASE about new year\n"));
+        assert!(written.contains("1 EXID 42\n2 TYPE https://gedcom.io/terms/v7/RIN\n"));
+        let mut parser = Parser::new(written);
+        let mut reparsed = parser.parse_record().unwrap();
+        assert_eq!(parser.version(), GedcomVersion::V7);
+        assert_eq!(reparsed.individuals[0].unique_ids, ["0F0E0D0C"]);
+
+        assert!(convert(&mut reparsed, GedcomVersion::V5).is_empty());
+        let individual = &reparsed.individuals[0];
+        assert_eq!(individual.record_file_number.as_deref(), Some("1234:5"));
+        assert_eq!(individual.extensions[0].tag, "_UID");
+        let birth = &individual.events[0];
+        assert_eq!(
+            birth.date.as_deref(),
+            Some("INT @#DJULIAN@ 1700 (about new year)")
+        );
+        assert_eq!(reparsed.notes[0].automated_record_id.as_deref(), Some("42"));
+        let file = &reparsed.multimedia[0].files[0];
+        assert_eq!(file.value.as_deref(), Some("C:/photos/my photo.jpg"));
+        assert_eq!(file.form.as_deref(), Some("jpg"));
+
+        let source = std::fs::read_to_string("./tests/fixtures/gedcom7.ged").unwrap();
+        let mut data = Parser::new(&source).parse_record().unwrap();
+        let report = convert(&mut data, GedcomVersion::V5);
+        let tags: Vec<&str> = report.iter().map(|mapping| mapping.tag.as_str()).collect();
+        assert_eq!(
+            tags,
+            [
+                "SCHMA", "EXID", "SEX", "PHRASE", "SDATE", "NO", "INIL", "CROP", "TITL", "TRAN",
+                "LANG", "TRAN"
+            ]
+        );
+        assert_eq!(
+            report[2].to_string(),
+            "@I1@ SEX: the sex X is written as N, which GEDCOM 5.5.1 does not define"
+        );
+        assert!(matches!(data.individuals[0].sex, Gender::Nonbinary));
+        let mut writer = GedcomWriter::new();
+        writer.write_data(&data);
+        assert!(writer.as_str().contains("\n1 SEX N\n"));
+        assert_eq!(report[5].xref.as_deref(), Some("@I1@"));
+        assert!(data.individuals[0].non_events.is_empty());
+    }
+
+    #[test]
+    fn reads_and_writes_gedzip() {
+        let jpeg = std::fs::read("./tests/fixtures/media/portrait.jpg").unwrap();
+        let png = std::fs::read("./tests/fixtures/media/portrait.png").unwrap();
+        let package = Gedzip::open("./tests/fixtures/gedzip.gdz").unwrap();
+        let names: Vec<&str> = package.names().collect();
+        assert_eq!(
+            names,
+            ["gedcom.ged", "media/portrait.jpg", "media/portrait.png"]
+        );
+        let mut data = package.parse().unwrap();
+        assert_eq!(data.individuals.len(), 1);
+        let file = &data.multimedia[0].files[0];
+        assert_eq!(file.value.as_deref(), Some("media/portrait.jpg"));
+        let resolve = |reference| package.resolve(reference).unwrap();
+        assert_eq!(
+            resolve("media/portrait.jpg").as_deref(),
+            Some(jpeg.as_slice())
+        );
+        assert_eq!(
+            resolve("./media//portrait.png").as_deref(),
+            Some(png.as_slice())
+        );
+        assert_eq!(resolve("../media/portrait.png"), None);
+        assert_eq!(resolve("https://example.com/portrait.png"), None);
+
+        let absolute = std::fs::canonicalize("./tests/fixtures/media/portrait.png").unwrap();
+        let mut media = Media::new(Some("@O2@".to_string()));
+        for value in [absolute.to_str().unwrap(), "https://example.com/a.png"] {
+            media.files.push(MediaFile {
+                value: Some(value.to_string()),
+                ..MediaFile::default()
+            });
+        }
+        data.multimedia.push(media);
+        let mut writer = GedzipWriter::new();
+        writer.write_data(&mut data, "./tests/fixtures").unwrap();
+        let bytes = writer.finish().unwrap();
+
+        let package = Gedzip::from_bytes(&bytes).unwrap();
+        let names: Vec<&str> = package.names().collect();
+        assert_eq!(
+            names,
+            [
+                "gedcom.ged",
+                "media/portrait.jpg",
+                "media/portrait.png",
+                "media/2-portrait.png"
+            ]
+        );
+        let mut parser = package.parser().unwrap();
+        let data = parser.parse_record().unwrap();
+        assert_eq!(parser.version(), GedcomVersion::V7);
+        let files = &data.multimedia[1].files;
+        assert_eq!(files[0].value.as_deref(), Some("media/2-portrait.png"));
+        assert_eq!(files[1].value.as_deref(), Some("https://example.com/a.png"));
+        assert_eq!(
+            package.resolve("media/2-portrait.png").unwrap().as_deref(),
+            Some(png.as_slice())
+        );
+
+        let error = Gedzip::from_bytes(b"0 HEAD\n0 TRLR\n").unwrap_err();
+        assert!(matches!(error, GedcomError::InvalidArchive { .. }));
+        assert_eq!(error.line(), None);
+        assert_eq!(
+            error.to_string(),
+            "Invalid archive: no end of central directory"
+        );
+        let error = Gedzip::open("./tests/fixtures/missing.gdz").unwrap_err();
+        assert!(
+            matches!(error, GedcomError::File { ref path, .. } if path.ends_with("missing.gdz"))
+        );
+        assert_eq!(error.line(), None);
+    }
+
+    /// The CRC-32 checksum zip archives keep of their entries
+    fn crc32(bytes: &[u8]) -> u32 {
+        let mut crc = !0_u32;
+        for &byte in bytes {
+            crc ^= u32::from(byte);
+            for _ in 0..8 {
+                crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+            }
+        }
+        !crc
+    }
+
+    /// A zip archive of entries compressed with deflate, given as their name, raw deflate data,
+    /// and the size and checksum declared for their contents
+    fn deflated_archive(entries: &[(&str, &[u8], u32, u32)]) -> Vec<u8> {
+        let mut archive = Vec::new();
+        let mut directory = Vec::new();
+        for &(name, data, size, crc) in entries {
+            let offset = archive.len() as u32;
+            // version needed, flags, method, time, date, checksum, sizes, name and extra lengths
+            let fields = [
+                &20_u16.to_le_bytes()[..],
+                &[0, 0],
+                &8_u16.to_le_bytes(),
+                &[0; 4],
+                &crc.to_le_bytes(),
+                &(data.len() as u32).to_le_bytes(),
+                &size.to_le_bytes(),
+                &(name.len() as u16).to_le_bytes(),
+                &[0, 0],
+            ]
+            .concat();
+            archive.extend(0x0403_4b50_u32.to_le_bytes());
+            archive.extend(&fields);
+            archive.extend(name.as_bytes());
+            archive.extend(data);
+            directory.extend(0x0201_4b50_u32.to_le_bytes());
+            directory.extend(20_u16.to_le_bytes());
+            directory.extend(&fields);
+            // comment length, disk number, internal and external attributes
+            directory.extend([0; 10]);
+            directory.extend(offset.to_le_bytes());
+            directory.extend(name.as_bytes());
+        }
+        let count = (entries.len() as u16).to_le_bytes();
+        let end = [
+            &0x0605_4b50_u32.to_le_bytes()[..],
+            &[0; 4],
+            &count,
+            &count,
+            &(directory.len() as u32).to_le_bytes(),
+            &(archive.len() as u32).to_le_bytes(),
+            &[0, 0],
+        ]
+        .concat();
+        [archive, directory, end].concat()
+    }
+
+    #[test]
+    fn inflates_deflate_blocks_of_every_kind() {
+        // a dynamic Huffman block with a single 1-bit distance code
+        let dynamic = [
+            77, 198, 177, 9, 0, 32, 12, 4, 192, 62, 83, 252, 8, 58, 130, 96, 192, 66, 20, 66, 22,
+            248, 232, 4, 217, 191, 176, 181, 187, 130, 161, 173, 75, 197, 218, 174, 96, 36, 15, 47,
+            35, 137, 207, 82, 224, 54, 77, 30,
+        ];
+        let gedcom = b"0 HEAD\n1 NOTE abracadabra abracadabra\n0 TRLR\n";
+        // fixed Huffman matches of 258 bytes 2 back and 10 bytes 1 back, copying what they write
+        let overlapping = [75, 76, 26, 133, 8, 0, 0];
+        let repeated = ["ab".repeat(130), "b".repeat(10)].concat();
+        // stored blocks, one of them empty, around a fixed Huffman block matching stored bytes
+        let stored = [
+            0, 7, 0, 248, 255, 48, 32, 72, 69, 65, 68, 10, 50, 84, 240, 243, 15, 113, 85, 0, 177,
+            185, 0, 0, 0, 0, 255, 255, 1, 7, 0, 248, 255, 48, 32, 84, 82, 76, 82, 10,
+        ];
+        let stored_text = b"0 HEAD\n1 NOTE HEAD\n0 TRLR\n";
+        let valid: [(&str, &[u8], &[u8]); 3] = [
+            ("gedcom.ged", &dynamic, gedcom),
+            ("overlapping", &overlapping, repeated.as_bytes()),
+            ("stored", &stored, stored_text),
+        ];
+        let corrupt: [(&str, &[u8], &str); 7] = [
+            ("distance", &[75, 4, 66, 0], "distance too far back"),
+            ("block type", &[7], "invalid block type"),
+            (
+                "stored length",
+                &[1, 3, 0, 3, 0, 97, 98, 99],
+                "invalid stored block length",
+            ),
+            // four code length codes of 1 bit
+            (
+                "oversubscribed",
+                &[5, 0, 146, 4],
+                "oversubscribed Huffman code",
+            ),
+            // two literal and length codes of 2 bits
+            (
+                "incomplete",
+                &[5, 128, 1, 5, 0, 0, 0, 128, 182, 246, 255, 68, 0],
+                "incomplete Huffman code",
+            ),
+            ("truncated", &dynamic[..45], "unexpected end of data"),
+            ("truncated header", &dynamic[..10], "unexpected end of data"),
+        ];
+        let entries: Vec<_> = (valid.iter())
+            .map(|&(name, data, contents)| (name, data, contents.len() as u32, crc32(contents)))
+            .chain(corrupt.iter().map(|&(name, data, _)| (name, data, 100, 0)))
+            .collect();
+        let package = Gedzip::from_bytes(&deflated_archive(&entries)).unwrap();
+
+        for (name, _, contents) in valid {
+            assert_eq!(package.entry(name).unwrap().unwrap(), contents, "{name}");
+        }
+        for (name, _, message) in corrupt {
+            assert_eq!(
+                package.entry(name).unwrap_err(),
+                GedcomError::InvalidArchive {
+                    message: format!("{name}: {message}"),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn inflates_gedzip_entries_to_their_declared_size() {
+        let gedcom = b"0 HEAD\n1 GEDC\n2 VERS 7.0\n0 TRLR\n";
+        let deflated_gedcom = [
+            51, 80, 240, 112, 117, 116, 225, 50, 84, 112, 119, 117, 113, 230, 50, 82, 8, 115, 13,
+            10, 86, 48, 215, 51, 224, 50, 80, 8, 9, 242, 9, 226, 2, 0,
+        ];
+        // 100 000 zero bytes, compressed a thousandfold
+        let zeros = [
+            &[
+                237, 193, 49, 1, 0, 0, 0, 194, 160, 245, 79, 109, 13, 15, 160,
+            ][..],
+            &[0; 96],
+            &[128, 87, 3],
+        ]
+        .concat();
+        let zeros_crc = crc32(&[0; 100_000]);
+        let archive = deflated_archive(&[
+            ("gedcom.ged", &deflated_gedcom, 32, crc32(gedcom)),
+            ("zeros.bin", &zeros, 100_000, zeros_crc),
+            ("oversized.bin", &zeros, 1000, zeros_crc),
+            ("undersized.bin", &zeros, 100_001, zeros_crc),
+            ("truncated.bin", &zeros[..50], 100_000, zeros_crc),
+        ]);
+
+        // entries are only decompressed once asked for
+        let package = Gedzip::from_bytes(&archive).unwrap();
+        assert_eq!(package.gedcom().unwrap(), gedcom);
+        assert_eq!(
+            package
+                .parse()
+                .unwrap()
+                .header
+                .unwrap()
+                .gedcom
+                .unwrap()
+                .version
+                .unwrap(),
+            "7.0"
+        );
+        let zeros = package.resolve("zeros.bin").unwrap().unwrap();
+        assert!(zeros.len() == 100_000 && zeros.iter().all(|&byte| byte == 0));
+        for (name, message) in [
+            ("oversized.bin", "more bytes than the declared size"),
+            ("undersized.bin", "fewer bytes than the declared size"),
+            ("truncated.bin", "unexpected end of data"),
+        ] {
+            assert_eq!(
+                package.entry(name).unwrap_err(),
+                GedcomError::InvalidArchive {
+                    message: format!("{name}: {message}"),
+                }
+            );
+        }
+    }
 }